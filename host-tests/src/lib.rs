@@ -0,0 +1,9 @@
+//! The modules of the workshop crate that depend on nothing board specific,
+//! built from their sources in `../src` for the host.
+
+#![no_std]
+
+#[path = "../../src/bmp280.rs"]
+pub mod bmp280;
+#[path = "../../src/rgb_led.rs"]
+pub mod rgb_led;
@@ -0,0 +1,76 @@
+//! A fake BMP280, answering from a register file.
+
+use embedded_hal_1::i2c::{ErrorType, I2c, Operation};
+
+/// Calibration of the datasheet's compensation example (section 3.12), in
+/// register order from `dig_T1` to `dig_P9`.
+pub const DATASHEET_CALIBRATION: [i32; 12] = [
+    27504, 26435, -1000, 36477, -10685, 3024, 2855, 140, -7, 15500, -14600, 6000,
+];
+/// Raw temperature of the datasheet example.
+pub const DATASHEET_ADC_T: u32 = 519888;
+/// Raw pressure of the datasheet example.
+pub const DATASHEET_ADC_P: u32 = 415148;
+
+/// Registers of a BMP280, written and read through the auto-incrementing
+/// register pointer like on the chip.
+pub struct FakeChip {
+    pub registers: [u8; 256],
+}
+
+impl FakeChip {
+    /// A chip with the datasheet calibration and raw readings.
+    pub fn datasheet() -> Self {
+        let mut chip = FakeChip {
+            registers: [0; 256],
+        };
+        chip.registers[0xD0] = 0x58;
+        for (i, value) in DATASHEET_CALIBRATION.iter().enumerate() {
+            let [low, high] = (*value as u16).to_le_bytes();
+            chip.registers[0x88 + 2 * i] = low;
+            chip.registers[0x89 + 2 * i] = high;
+        }
+        chip.set_raw(DATASHEET_ADC_T, DATASHEET_ADC_P);
+        chip
+    }
+
+    /// Sets the 20 bit raw readings of the data registers.
+    pub fn set_raw(&mut self, adc_t: u32, adc_p: u32) {
+        for (start, adc) in [(0xF7, adc_p), (0xFA, adc_t)] {
+            self.registers[start] = (adc >> 12) as u8;
+            self.registers[start + 1] = (adc >> 4) as u8;
+            self.registers[start + 2] = ((adc & 0xF) << 4) as u8;
+        }
+    }
+}
+
+impl ErrorType for FakeChip {
+    type Error = core::convert::Infallible;
+}
+
+impl I2c for FakeChip {
+    fn transaction(
+        &mut self,
+        _address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut pointer = 0;
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    pointer = bytes[0] as usize;
+                    for (i, byte) in bytes[1..].iter().enumerate() {
+                        self.registers[(pointer + i) % 256] = *byte;
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for byte in buffer.iter_mut() {
+                        *byte = self.registers[pointer % 256];
+                        pointer += 1;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
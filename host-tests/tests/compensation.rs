@@ -0,0 +1,58 @@
+//! The integer compensation against the floating point one, over the whole
+//! operating range of the chip.
+
+mod common;
+
+use common::FakeChip;
+use embedded_nov_2024::bmp280::BMP280;
+
+#[test]
+fn integer_and_float_compensation_agree() {
+    let mut checked = 0;
+    for adc_t in (300_000..=650_000).step_by(2_500) {
+        for adc_p in (150_000..=700_000).step_by(2_500) {
+            let mut chip = FakeChip::datasheet();
+            chip.set_raw(adc_t, adc_p);
+            let mut bmp = BMP280::new(chip).unwrap();
+            let measurement = bmp.measure().unwrap();
+
+            // Only the readings a real chip can give, -40 to 85 °C and 300
+            // to 1100 hPa
+            if !(-40.0..=85.0).contains(&measurement.temperature_c)
+                || !(30_000.0..=110_000.0).contains(&measurement.pressure_pa)
+            {
+                continue;
+            }
+            checked += 1;
+
+            let millicelsius = bmp.temp_millicelsius().unwrap();
+            assert!(
+                (measurement.temperature_c * 1000.0 - millicelsius as f64).abs() < 1.0,
+                "raw {adc_t}: {} °C vs {millicelsius} m°C",
+                measurement.temperature_c
+            );
+
+            let pressure_pa = bmp
+                .pressure_pa_int()
+                .unwrap()
+                .expect("a pressure in range overflowed");
+            assert!(
+                (measurement.pressure_pa - pressure_pa as f64).abs() < 1.5,
+                "raw {adc_t}, {adc_p}: {} Pa vs {pressure_pa} Pa",
+                measurement.pressure_pa
+            );
+        }
+    }
+    assert!(checked > 10_000, "only {checked} readings in range");
+}
+
+#[test]
+fn integer_pressure_without_calibration_is_none() {
+    let mut chip = FakeChip::datasheet();
+    // dig_P1 of a blank calibration, the divisor of the datasheet's formula
+    chip.registers[0x8E] = 0;
+    chip.registers[0x8F] = 0;
+    let mut bmp = BMP280::new(chip).unwrap();
+
+    assert_eq!(bmp.pressure_pa_int().unwrap(), None);
+}
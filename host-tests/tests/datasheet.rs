@@ -0,0 +1,24 @@
+//! The compensation example of the datasheet, section 3.12.
+
+mod common;
+
+use common::FakeChip;
+use embedded_nov_2024::bmp280::BMP280;
+
+#[test]
+fn integer_compensation_matches_the_datasheet() {
+    let mut bmp = BMP280::new(FakeChip::datasheet()).unwrap();
+
+    assert_eq!(bmp.temp_millicelsius().unwrap(), 25080);
+    assert_eq!(bmp.pressure_pa_int().unwrap(), Some(100653));
+}
+
+#[test]
+fn float_compensation_matches_the_datasheet() {
+    let mut bmp = BMP280::new(FakeChip::datasheet()).unwrap();
+    let measurement = bmp.measure().unwrap();
+
+    assert!((measurement.temperature_c - 25.08).abs() < 0.005);
+    // The datasheet rounds its double precision result to 100653.27 Pa
+    assert!((measurement.pressure_pa - 100653.27).abs() < 0.05);
+}
@@ -0,0 +1,143 @@
+//! Text of the smart clock screens and menu.
+
+mod rtc;
+
+// The clock module looks these up in its parent, the bin crate root
+use rtc::{DateTime, DayOfWeek};
+
+#[allow(dead_code)]
+#[path = "../../src/smart_clock/format.rs"]
+mod format;
+
+use format::{
+    format_temperature, format_threshold, format_time, TemperatureUnit, Threshold, TimeFormat,
+};
+
+fn at(hour: u8, minute: u8, second: u8) -> DateTime {
+    DateTime {
+        year: 2024,
+        month: 11,
+        day: 14,
+        day_of_week: DayOfWeek::Thursday,
+        hour,
+        minute,
+        second,
+    }
+}
+
+#[test]
+fn midnight() {
+    assert_eq!(format_time(&at(0, 0, 0), TimeFormat::H24), "00:00:00");
+    assert_eq!(format_time(&at(0, 0, 0), TimeFormat::H12), "12:00:00 AM");
+    assert_eq!(format_time(&at(0, 59, 59), TimeFormat::H12), "12:59:59 AM");
+}
+
+#[test]
+fn noon() {
+    assert_eq!(format_time(&at(12, 0, 0), TimeFormat::H24), "12:00:00");
+    assert_eq!(format_time(&at(12, 0, 0), TimeFormat::H12), "12:00:00 PM");
+    assert_eq!(format_time(&at(11, 59, 59), TimeFormat::H12), "11:59:59 AM");
+}
+
+#[test]
+fn afternoon_and_evening() {
+    assert_eq!(format_time(&at(13, 5, 9), TimeFormat::H24), "13:05:09");
+    assert_eq!(format_time(&at(13, 5, 9), TimeFormat::H12), "01:05:09 PM");
+    assert_eq!(format_time(&at(23, 59, 59), TimeFormat::H12), "11:59:59 PM");
+}
+
+#[test]
+fn every_hour_fits() {
+    for hour in 0..24 {
+        for format in [TimeFormat::H24, TimeFormat::H12] {
+            let text = format_time(&at(hour, 59, 59), format);
+            assert!(text.starts_with(|c: char| c.is_ascii_digit()), "{text}");
+        }
+    }
+}
+
+#[test]
+fn temperature() {
+    assert_eq!(
+        format_temperature(23.46, TemperatureUnit::Celsius),
+        "23.5°C  "
+    );
+    assert_eq!(
+        format_temperature(0.0, TemperatureUnit::Fahrenheit),
+        "32.0°F  "
+    );
+    assert_eq!(
+        format_temperature(100.0, TemperatureUnit::Fahrenheit),
+        "212.0°F  "
+    );
+}
+
+#[test]
+fn negative_temperature() {
+    assert_eq!(
+        format_temperature(-5.25, TemperatureUnit::Celsius),
+        "-5.2°C  "
+    );
+    assert_eq!(
+        format_temperature(-40.0, TemperatureUnit::Fahrenheit),
+        "-40.0°F  "
+    );
+    assert_eq!(
+        format_temperature(-20.0, TemperatureUnit::Fahrenheit),
+        "-4.0°F  "
+    );
+}
+
+#[test]
+fn half_degree_thresholds() {
+    let threshold = Threshold::from_celsius(22).increment();
+    assert_eq!(threshold.celsius(), 22.5);
+    assert_eq!(
+        format_threshold(threshold, TemperatureUnit::Celsius),
+        "22.5°C"
+    );
+    assert_eq!(
+        format_threshold(threshold, TemperatureUnit::Fahrenheit),
+        "72.5°F"
+    );
+    assert_eq!(
+        format_threshold(threshold.decrement(), TemperatureUnit::Celsius),
+        "22.0°C"
+    );
+}
+
+#[test]
+fn negative_thresholds() {
+    let half_below = Threshold::from_celsius(0).decrement();
+    assert_eq!(
+        format_threshold(half_below, TemperatureUnit::Celsius),
+        "-0.5°C"
+    );
+    assert_eq!(
+        format_threshold(half_below, TemperatureUnit::Fahrenheit),
+        "31.1°F"
+    );
+    let frost = Threshold::from_celsius(-18).decrement();
+    assert_eq!(format_threshold(frost, TemperatureUnit::Celsius), "-18.5°C");
+    assert_eq!(
+        format_threshold(frost, TemperatureUnit::Fahrenheit),
+        "-1.3°F"
+    );
+}
+
+#[test]
+fn thresholds_match_the_temperatures() {
+    for half_degrees in -100..=100 {
+        let threshold = Threshold(half_degrees);
+        for unit in [TemperatureUnit::Celsius, TemperatureUnit::Fahrenheit] {
+            let temperature = format_temperature(threshold.celsius(), unit);
+            assert!(temperature.starts_with(format_threshold(threshold, unit).as_str()));
+        }
+    }
+}
+
+#[test]
+fn thresholds_saturate() {
+    assert_eq!(Threshold(i16::MAX).increment().0, i16::MAX);
+    assert_eq!(Threshold(i16::MIN).decrement().0, i16::MIN);
+}
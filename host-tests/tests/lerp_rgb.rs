@@ -0,0 +1,36 @@
+//! Color interpolation of the RGB LED fades.
+
+use embedded_nov_2024::rgb_led::lerp_rgb;
+
+const RED: (u16, u16, u16) = (255, 0, 0);
+const BLUE: (u16, u16, u16) = (0, 0, 255);
+
+#[test]
+fn ends_are_the_colors() {
+    assert_eq!(lerp_rgb(RED, BLUE, 0.0), RED);
+    assert_eq!(lerp_rgb(RED, BLUE, 1.0), BLUE);
+}
+
+#[test]
+fn midpoint_is_halfway() {
+    assert_eq!(lerp_rgb(RED, BLUE, 0.5), (127, 0, 127));
+    assert_eq!(lerp_rgb((0, 100, 200), (100, 200, 0), 0.5), (50, 150, 100));
+}
+
+#[test]
+fn same_color_stays() {
+    for t in [0.0, 0.25, 0.5, 0.75, 1.0] {
+        assert_eq!(lerp_rgb((12, 34, 56), (12, 34, 56), t), (12, 34, 56));
+    }
+}
+
+#[test]
+fn channels_move_monotonically() {
+    let mut previous = lerp_rgb(RED, BLUE, 0.0);
+    for step in 1..=100 {
+        let color = lerp_rgb(RED, BLUE, step as f32 / 100.0);
+        assert!(color.0 <= previous.0 && color.2 >= previous.2);
+        assert_eq!(color.1, 0);
+        previous = color;
+    }
+}
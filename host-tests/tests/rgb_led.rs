@@ -0,0 +1,113 @@
+//! The RGB LED driver against mock PWM channels.
+
+use embassy_time::Duration;
+use embedded_hal_mock::eh1::pwm::{Mock, Transaction};
+use embedded_nov_2024::rgb_led::RgbLed;
+
+/// A channel expected to be set to each of `duties` in turn, out of 255.
+fn channel(duties: &[u16]) -> Mock {
+    let expectations: Vec<_> = duties
+        .iter()
+        .flat_map(|&duty| {
+            [
+                Transaction::max_duty_cycle(255),
+                Transaction::set_duty_cycle(duty),
+            ]
+        })
+        .collect();
+    Mock::new(&expectations)
+}
+
+/// Checks that every expected duty cycle was set.
+fn done(mut channels: [Mock; 3]) {
+    for channel in &mut channels {
+        channel.done();
+    }
+}
+
+#[test]
+fn starts_off() {
+    let channels = [channel(&[255]), channel(&[255]), channel(&[255])];
+    let [r, g, b] = channels.clone();
+
+    let led = RgbLed::new(r, g, b);
+    assert_eq!(led.color(), (0, 0, 0));
+    assert_eq!(led.brightness(), 100);
+    done(channels);
+}
+
+#[test]
+fn duty_cycles_are_inverted_for_the_common_anode() {
+    let channels = [
+        channel(&[255, 0]),
+        channel(&[255, 127]),
+        channel(&[255, 255]),
+    ];
+    let [r, g, b] = channels.clone();
+
+    let mut led = RgbLed::new(r, g, b);
+    led.set_rgb(255, 128, 0);
+    assert_eq!(led.color(), (255, 128, 0));
+    done(channels);
+}
+
+#[test]
+fn channels_above_255_are_clamped() {
+    let channels = [channel(&[255, 0]), channel(&[255, 0]), channel(&[255, 0])];
+    let [r, g, b] = channels.clone();
+
+    let mut led = RgbLed::new(r, g, b);
+    led.set_rgb(256, 1000, u16::MAX);
+    done(channels);
+}
+
+#[test]
+fn brightness_scales_the_current_and_next_colors() {
+    let channels = [
+        channel(&[255, 55, 155, 205]),
+        channel(&[255, 155, 205, 255]),
+        channel(&[255, 255, 255, 155]),
+    ];
+    let [r, g, b] = channels.clone();
+
+    let mut led = RgbLed::new(r, g, b);
+    led.set_rgb(200, 100, 0);
+    led.set_brightness(50);
+    assert_eq!(led.brightness(), 50);
+    // The color is kept at full scale, only the output is dimmed
+    assert_eq!(led.color(), (200, 100, 0));
+    led.set_rgb(100, 0, 200);
+    done(channels);
+}
+
+#[test]
+fn brightness_is_capped_at_100() {
+    let channels = [
+        channel(&[255, 0, 0]),
+        channel(&[255, 0, 0]),
+        channel(&[255, 0, 0]),
+    ];
+    let [r, g, b] = channels.clone();
+
+    let mut led = RgbLed::new(r, g, b);
+    led.set_rgb(255, 255, 255);
+    led.set_brightness(150);
+    assert_eq!(led.brightness(), 100);
+    done(channels);
+}
+
+#[test]
+fn fade_ends_on_the_target() {
+    // Two frames of 20 ms, halfway then on the target
+    let channels = [
+        channel(&[255, 128, 0]),
+        channel(&[255, 255, 255]),
+        channel(&[255, 255, 255]),
+    ];
+    let [r, g, b] = channels.clone();
+
+    let mut led = RgbLed::new(r, g, b);
+    embassy_futures::block_on(led.fade_to((255, 0, 0), Duration::from_millis(40)));
+    assert_eq!(led.color(), (255, 0, 0));
+    done(channels);
+}
@@ -0,0 +1,251 @@
+//! Reading the time server responses of the smart clock.
+
+mod rtc;
+
+// The clock module looks these up in its parent, the bin crate root
+use rtc::{DateTime, DayOfWeek};
+
+#[allow(dead_code)]
+#[path = "../../src/smart_clock/time_api.rs"]
+mod time_api;
+
+use time_api::{datetime, from_timestamp, parse_iso8601, parse_time, timestamp, ApiResponse};
+
+/// 2024-11-14 13:05:09 UTC, a Thursday.
+fn expected() -> DateTime {
+    DateTime {
+        year: 2024,
+        month: 11,
+        day: 14,
+        day_of_week: DayOfWeek::Thursday,
+        hour: 13,
+        minute: 5,
+        second: 9,
+    }
+}
+
+fn parse(body: &str) -> Option<DateTime> {
+    parse_time(body.as_bytes(), None)
+}
+
+#[test]
+fn nested() {
+    let body =
+        r#"{"date":{"year":2024,"month":11,"day":14},"time":{"hour":13,"minite":5,"second":9}}"#;
+    assert_eq!(parse(body), Some(expected()));
+}
+
+#[test]
+fn nested_with_unknown_fields() {
+    let body = r#"{"status":"ok","date":{"year":2024,"month":11,"day":14,"week":46},"time":{"hour":13,"minite":5,"second":9,"ms":120}}"#;
+    assert_eq!(parse(body), Some(expected()));
+}
+
+#[test]
+fn flat() {
+    let body = r#"{"year":2024,"month":11,"day":14,"hour":13,"minute":5,"second":9}"#;
+    assert_eq!(parse(body), Some(expected()));
+}
+
+#[test]
+fn flat_aliases() {
+    let body = r#"{"year":2024,"month":11,"day":14,"hour":13,"minite":5,"seconds":9}"#;
+    assert_eq!(parse(body), Some(expected()));
+}
+
+#[test]
+fn partial_responses_are_completed() {
+    let fallback = DateTime {
+        second: 59,
+        ..expected()
+    };
+    let body = r#"{"time":{"hour":13,"minite":5}}"#;
+    assert_eq!(
+        parse_time(body.as_bytes(), Some(&fallback)),
+        Some(DateTime {
+            second: 59,
+            ..expected()
+        })
+    );
+    let body = r#"{"hour":13,"minute":5,"second":9}"#;
+    assert_eq!(
+        parse_time(body.as_bytes(), Some(&fallback)),
+        Some(expected())
+    );
+    // Nothing to complete from
+    assert_eq!(parse(r#"{"time":{"hour":13,"minite":5}}"#), None);
+}
+
+#[test]
+fn nothing_sent() {
+    let fallback = expected();
+    let (response, _used) = serde_json_core::de::from_str::<ApiResponse>("{}").unwrap();
+    assert_eq!(response.datetime(Some(&fallback)), None);
+    assert_eq!(parse_time(b"{}", Some(&fallback)), None);
+    assert_eq!(parse_time(br#"{"status":"ok"}"#, Some(&fallback)), None);
+}
+
+#[test]
+fn iso_utc() {
+    assert_eq!(parse_iso8601("2024-11-14T13:05:09Z"), Some(expected()));
+    assert_eq!(parse_iso8601("2024-11-14T13:05:09"), Some(expected()));
+    assert_eq!(parse_iso8601("2024-11-14 13:05:09"), Some(expected()));
+    assert_eq!(
+        parse(r#"{"datetime":"2024-11-14T13:05:09Z"}"#),
+        Some(expected())
+    );
+    assert_eq!(
+        parse(r#"{"dateTime":"2024-11-14T13:05:09"}"#),
+        Some(expected())
+    );
+}
+
+#[test]
+fn iso_offsets() {
+    assert_eq!(parse_iso8601("2024-11-14T15:05:09+02:00"), Some(expected()));
+    assert_eq!(parse_iso8601("2024-11-14T15:05:09+0200"), Some(expected()));
+    assert_eq!(parse_iso8601("2024-11-14T07:35:09-05:30"), Some(expected()));
+}
+
+#[test]
+fn iso_offsets_roll_the_date_over() {
+    let new_year = DateTime {
+        year: 2025,
+        month: 1,
+        day: 1,
+        day_of_week: DayOfWeek::Wednesday,
+        hour: 2,
+        minute: 0,
+        second: 0,
+    };
+    assert_eq!(parse_iso8601("2024-12-31T20:30:00-05:30"), Some(new_year));
+    let leap_day = DateTime {
+        year: 2024,
+        month: 2,
+        day: 29,
+        day_of_week: DayOfWeek::Thursday,
+        hour: 23,
+        minute: 0,
+        second: 0,
+    };
+    assert_eq!(parse_iso8601("2024-03-01T01:00:00+02:00"), Some(leap_day));
+}
+
+#[test]
+fn iso_fractional_seconds() {
+    assert_eq!(parse_iso8601("2024-11-14T13:05:09.5Z"), Some(expected()));
+    assert_eq!(
+        parse_iso8601("2024-11-14T15:05:09.123456+02:00"),
+        Some(expected())
+    );
+    assert_eq!(
+        parse(r#"{"datetime":"2024-11-14T15:05:09.999999+02:00","utc_offset":"+02:00"}"#),
+        Some(expected())
+    );
+}
+
+#[test]
+fn iso_goes_first() {
+    let body = r#"{"year":2000,"month":1,"day":1,"hour":0,"minute":0,"seconds":0,"dateTime":"2024-11-14T13:05:09"}"#;
+    assert_eq!(parse(body), Some(expected()));
+}
+
+#[test]
+fn malformed_json() {
+    assert_eq!(parse(""), None);
+    assert_eq!(parse("not json"), None);
+    assert_eq!(parse(r#"{"year":2024,"month":11"#), None);
+    assert_eq!(parse(r#"{"year":"2024","month":11,"day":14}"#), None);
+    assert_eq!(parse(r#"{"date":[2024,11,14]}"#), None);
+}
+
+#[test]
+fn malformed_iso() {
+    for s in [
+        "",
+        "2024-11-14",
+        "2024-11-14T13:05",
+        "2024/11/14T13:05:09",
+        "2024-11-14X13:05:09",
+        "2024-11-14T13-05-09",
+        "2024-1a-14T13:05:09",
+        "2024-11-14T+3:05:09",
+        "+024-11-14T13:05:09",
+        "2024-11-14T13:05:09.",
+        "2024-11-14T13:05:09 ",
+        "2024-11-14T13:05:09z",
+        "2024-11-14T13:05:09+2",
+        "2024-11-14T13:05:09+02:0",
+        "2024-11-14T13:05:09+02-00",
+        "2024-11-14T13:05:09+0a:00",
+        "2024-11-14T13:05:09+02:00Z",
+    ] {
+        assert_eq!(parse_iso8601(s), None, "{s}");
+    }
+}
+
+#[test]
+fn out_of_range_fields() {
+    for s in [
+        "2024-00-14T13:05:09Z",
+        "2024-13-14T13:05:09Z",
+        "2024-11-00T13:05:09Z",
+        "2024-11-31T13:05:09Z",
+        "2024-11-14T24:00:00Z",
+        "2024-11-14T13:60:09Z",
+        "2024-11-14T13:05:60Z",
+        "2024-11-14T13:05:09+24:00",
+        "2024-11-14T13:05:09+02:60",
+    ] {
+        assert_eq!(parse_iso8601(s), None, "{s}");
+    }
+    assert_eq!(
+        parse(
+            r#"{"date":{"year":2024,"month":13,"day":14},"time":{"hour":13,"minite":5,"second":9}}"#
+        ),
+        None
+    );
+    assert_eq!(
+        parse(r#"{"year":2024,"month":11,"day":14,"hour":13,"minute":5,"second":300}"#),
+        None
+    );
+    // Too large for the RTC fields, rather than wrapped around into range
+    assert_eq!(
+        parse(r#"{"year":2024,"month":267,"day":14,"hour":13,"minute":5,"second":9}"#),
+        None
+    );
+}
+
+#[test]
+fn leap_years() {
+    assert!(datetime(2024, 2, 29, 0, 0, 0).is_some());
+    assert!(datetime(2000, 2, 29, 0, 0, 0).is_some());
+    assert!(datetime(2023, 2, 29, 0, 0, 0).is_none());
+    assert!(datetime(2100, 2, 29, 0, 0, 0).is_none());
+    assert!(datetime(2024, 2, 30, 0, 0, 0).is_none());
+    assert_eq!(parse_iso8601("2024-02-31T00:00:00Z"), None);
+    assert_eq!(parse_iso8601("2023-02-29T00:00:00Z"), None);
+}
+
+#[test]
+fn days_of_the_week() {
+    let day = |year, month, day| datetime(year, month, day, 0, 0, 0).unwrap().day_of_week;
+    assert_eq!(day(1970, 1, 1), DayOfWeek::Thursday);
+    assert_eq!(day(2000, 2, 29), DayOfWeek::Tuesday);
+    assert_eq!(day(2024, 11, 14), DayOfWeek::Thursday);
+    assert_eq!(day(2024, 12, 1), DayOfWeek::Sunday);
+}
+
+#[test]
+fn timestamps() {
+    let epoch = datetime(1970, 1, 1, 0, 0, 0).unwrap();
+    assert_eq!(timestamp(&epoch), 0);
+    assert_eq!(timestamp(&expected()), 1_731_589_509);
+    assert_eq!(from_timestamp(1_731_589_509), Some(expected()));
+    // Past the years a u16 holds
+    assert_eq!(from_timestamp(i64::MAX / 2), None);
+    for seconds in (0..4_000_000_000i64).step_by(86_399 * 7) {
+        let datetime = from_timestamp(seconds).unwrap();
+        assert_eq!(timestamp(&datetime), seconds);
+    }
+}
@@ -0,0 +1,55 @@
+//! LED blinking shared by the examples. Embassy tasks can't live in a library,
+//! so wrap the helper in a task in the binary:
+//!
+//! ```ignore
+//! #[embassy_executor::task]
+//! async fn blink(pin: AnyPin) {
+//!     blink_forever(pin, Duration::from_millis(150), Duration::from_millis(150)).await
+//! }
+//! ```
+
+use core::future::Future;
+
+use embassy_rp::gpio::{AnyPin, Level, Output};
+use embassy_time::{Duration, Timer};
+
+/// An LED that can be switched on and off
+pub trait Led {
+    /// Lights the LED if `on`, turns it off otherwise
+    fn set(&mut self, on: bool) -> impl Future<Output = ()>;
+}
+
+impl Led for Output<'_> {
+    async fn set(&mut self, on: bool) {
+        self.set_level(Level::from(on));
+    }
+}
+
+/// Lets the owner of an LED blink it for a while and take it back afterwards
+impl<L: Led> Led for &mut L {
+    async fn set(&mut self, on: bool) {
+        (**self).set(on).await;
+    }
+}
+
+/// The Pico W onboard LED, wired to the WiFi chip's GPIO 0
+impl Led for cyw43::Control<'_> {
+    async fn set(&mut self, on: bool) {
+        self.gpio_set(0, on).await;
+    }
+}
+
+/// Blinks the LED on `pin`, keeping it lit for `on` and dark for `off`
+pub async fn blink_forever(pin: AnyPin, on: Duration, off: Duration) -> ! {
+    blink_led_forever(Output::new(pin, Level::Low), on, off).await
+}
+
+/// Like [`blink_forever`], for any [`Led`]
+pub async fn blink_led_forever(mut led: impl Led, on: Duration, off: Duration) -> ! {
+    loop {
+        led.set(true).await;
+        Timer::after(on).await;
+        led.set(false).await;
+        Timer::after(off).await;
+    }
+}
@@ -1,22 +1,133 @@
 //! A platform agnostic driver to interface with the BMP280 (pressure sensor)
 //!
 //! This driver is built using [`embedded-hal`] traits.
+//!
+//! [`embedded-hal`]: https://docs.rs/embedded-hal
+//!
+//! ## Example
+//!
+//! Configuring the weather monitoring settings by hand (or with
+//! [`BMP280::apply_preset`]) and taking a reading:
+//!
+//! ```
+//! # use embedded_hal_mock::eh1::delay::NoopDelay;
+//! # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+//! # let read = |register: u8, data: &[u8]| {
+//! #     [
+//! #         Transaction::transaction_start(0x76),
+//! #         Transaction::write(0x76, vec![register]),
+//! #         Transaction::read(0x76, data.to_vec()),
+//! #         Transaction::transaction_end(0x76),
+//! #     ]
+//! # };
+//! # let write = |register: u8, value: u8| {
+//! #     [Transaction::write_read(0x76, vec![register, value], vec![0])]
+//! # };
+//! # // The chip of the compensation example of the datasheet
+//! # let calibration = [
+//! #     0x70, 0x6b, 0x43, 0x67, 0x18, 0xfc, 0x7d, 0x8e, 0x43, 0xd6, 0xd0, 0x0b,
+//! #     0x27, 0x0b, 0x8c, 0x00, 0xf9, 0xff, 0x8c, 0x3c, 0xf8, 0xc6, 0x70, 0x17,
+//! # ];
+//! # let data = [0x65, 0x5a, 0xc0, 0x7e, 0xed, 0x00];
+//! # let expectations = [
+//! #     &read(0xD0, &[0x58])[..],
+//! #     &read(0x88, &calibration),
+//! #     &write(0xF5, 0x00),
+//! #     &write(0xF4, 0x24),
+//! #     &read(0xF4, &[0x24]),
+//! #     &write(0xF4, 0x25),
+//! #     &read(0xF3, &[0x00]),
+//! #     &read(0xF7, &data),
+//! # ]
+//! # .concat();
+//! # let mut mock = Mock::new(&expectations);
+//! # let i2c = mock.clone();
+//! # let mut delay = NoopDelay::new();
+//! use embedded_nov_2024::bmp280::*;
+//!
+//! let mut bmp = BMP280::new(i2c)?;
+//! bmp.set_config(Config {
+//!     t_sb: Standby::ms0_5,
+//!     filter: Filter::off,
+//! });
+//! bmp.set_control(Control {
+//!     osrs_t: Oversampling::x1,
+//!     osrs_p: Oversampling::x1,
+//!     mode: PowerMode::Sleep,
+//! });
+//!
+//! let measurement = bmp.measure_forced(&mut delay)?;
+//! assert_eq!(measurement.temperature_c, 25.08);
+//! assert_eq!(measurement.pressure_pa.round(), 100653.0);
+//! # mock.done();
+//! # Ok::<(), embedded_hal_1::i2c::ErrorKind>(())
+//! ```
 
 use core::fmt;
+use core::marker::PhantomData;
+use core::ops::RangeInclusive;
+use ehal::i2c::Operation;
 use embedded_hal_1 as ehal;
 
 /// The default address for the BMP280
 const DEFAULT_ADDRESS: u8 = 0x76;
 
-/// BMP280 driver
-pub struct BMP280<I2C: ehal::i2c::I2c> {
+/// Value of the id register on a BMP280
+const CHIP_ID_BMP280: u8 = 0x58;
+
+/// Written to the reset register to trigger a power-on reset
+const RESET_MAGIC: u8 = 0xB6;
+
+/// Status bit set while a conversion is running
+const STATUS_MEASURING_MASK: u8 = 0b00001000;
+
+/// Status bit set while the NVM data is copied to the image registers
+const STATUS_IM_UPDATE_MASK: u8 = 0b00000001;
+
+/// How often the status is polled while waiting for a conversion, in µs
+const CONVERSION_POLL_US: u32 = 500;
+
+/// Time from power-on until the chip responds, in µs
+const STARTUP_TIME_US: u32 = 2000;
+
+/// Pascals in one inch of mercury
+const PA_PER_INHG: f64 = 3386.389;
+
+/// Pascals in one millimeter of mercury
+const PA_PER_MMHG: f64 = 133.322_387_415;
+
+/// Altitude change per Pascal near sea level, in meters
+const METERS_PER_PA: f64 = 0.0843;
+
+/// Specific gas constant of dry air, in J/(kg·K)
+const R_DRY_AIR: f64 = 287.05;
+
+/// Air density at sea level in the standard atmosphere, in kg/m³
+const ISA_SEA_LEVEL_DENSITY: f64 = 1.225;
+
+/// Forced readings averaged into the relative altitude reference
+const REFERENCE_SAMPLES: u8 = 8;
+
+/// Typical RMS pressure noise in Pa (datasheet table 7), rows by pressure
+/// oversampling from x1 to x16, columns by IIR filter from off to 16
+const PRESSURE_NOISE_PA: [[f64; 5]; 5] = [
+    [6.6, 3.8, 2.5, 1.8, 1.2],
+    [5.0, 2.9, 1.9, 1.4, 1.0],
+    [3.8, 2.2, 1.5, 1.1, 0.7],
+    [2.9, 1.7, 1.1, 0.8, 0.6],
+    [2.4, 1.4, 0.9, 0.7, 0.5],
+];
+
+/// BMP280 driver. `MODE` can restrict it to the normal or forced mode API,
+/// see [`mode`]
+pub struct BMP280<I2C: ehal::i2c::I2c, MODE = mode::Any> {
     com: I2C,
     addr: u8,
     // Temperature compensation
     dig_t1: u16,
     dig_t2: i16,
     dig_t3: i16,
-    _t_fine: i32,
+    t_fine: i32,
     // Pressure calibration
     dig_p1: u16,
     dig_p2: i16,
@@ -27,6 +138,19 @@ pub struct BMP280<I2C: ehal::i2c::I2c> {
     dig_p7: i16,
     dig_p8: i16,
     dig_p9: i16,
+    // Whether the coefficients above were read from the chip
+    calibrated: bool,
+    // Ground level pressure for the relative altitude, in Pa
+    reference_pa: Option<f64>,
+    // Register reads as a write then a read rather than one transaction
+    split_reads: Option<SplitReads>,
+    // Last settings written, until a reset
+    control: Option<Control>,
+    config: Option<Config>,
+    // User corrections added to every reading, in m°C and Pa
+    temp_offset_mc: i32,
+    pressure_offset_pa: i32,
+    mode: PhantomData<MODE>,
 }
 
 impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
@@ -35,13 +159,73 @@ impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
     where
         I2C: ehal::i2c::I2c<Error = E>,
     {
-        let mut chip = BMP280 {
+        let mut chip = Self::uncalibrated(i2c, addr);
+        if chip.id()? == CHIP_ID_BMP280 {
+            chip.read_calibration();
+        }
+
+        Ok(chip)
+    }
+
+    /// Like [`new_with_address`](Self::new_with_address), also applying
+    /// `control` and `config` so the first reading is already meaningful
+    pub fn new_configured<E>(
+        i2c: I2C,
+        addr: u8,
+        control: Control,
+        config: Config,
+    ) -> Result<BMP280<I2C>, E>
+    where
+        I2C: ehal::i2c::I2c<Error = E>,
+    {
+        let mut chip = Self::new_with_address(i2c, addr)?;
+        chip.apply_settings(control, config);
+
+        Ok(chip)
+    }
+
+    /// Like [`new_with_address`](Self::new_with_address), first waiting for
+    /// the chip's power-on startup time
+    pub fn new_with_delay<E>(
+        i2c: I2C,
+        addr: u8,
+        delay: &mut impl ehal::delay::DelayNs,
+    ) -> Result<BMP280<I2C>, E>
+    where
+        I2C: ehal::i2c::I2c<Error = E>,
+    {
+        delay.delay_us(STARTUP_TIME_US);
+        Self::new_with_address(i2c, addr)
+    }
+
+    /// Like [`new_with_address`](Self::new_with_address), first waiting up to
+    /// `max_wait_us` for the chip to copy its trimming parameters from NVM
+    pub fn new_when_ready<E>(
+        i2c: I2C,
+        addr: u8,
+        delay: &mut impl ehal::delay::DelayNs,
+        max_wait_us: u32,
+    ) -> Result<BMP280<I2C>, E>
+    where
+        I2C: ehal::i2c::I2c<Error = E>,
+    {
+        let mut chip = Self::uncalibrated(i2c, addr);
+        if chip.id()? == CHIP_ID_BMP280 && chip.wait_until_ready(delay, max_wait_us)? {
+            chip.read_calibration();
+        }
+
+        Ok(chip)
+    }
+
+    fn uncalibrated(i2c: I2C, addr: u8) -> BMP280<I2C> {
+        check_address(addr);
+        BMP280 {
             com: i2c,
             addr,
             dig_t1: 0,
             dig_t2: 0,
             dig_t3: 0,
-            _t_fine: 0,
+            t_fine: 0,
             dig_p1: 0,
             dig_p2: 0,
             dig_p3: 0,
@@ -51,15 +235,42 @@ impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
             dig_p7: 0,
             dig_p8: 0,
             dig_p9: 0,
-        };
+            calibrated: false,
+            reference_pa: None,
+            split_reads: None,
+            control: None,
+            config: None,
+            temp_offset_mc: 0,
+            pressure_offset_pa: 0,
+            mode: PhantomData,
+        }
+    }
 
-        if chip.id() == 0x58 {
+    /// Like [`new_with_address`](Self::new_with_address), reading the
+    /// registers as configured by `split` from the start, see
+    /// [`set_split_reads`](Self::set_split_reads)
+    pub fn new_with_split_reads<E>(i2c: I2C, addr: u8, split: SplitReads) -> Result<BMP280<I2C>, E>
+    where
+        I2C: ehal::i2c::I2c<Error = E>,
+    {
+        let mut chip = Self::uncalibrated(i2c, addr);
+        chip.split_reads = Some(split);
+        if chip.id()? == CHIP_ID_BMP280 {
             chip.read_calibration();
         }
 
         Ok(chip)
     }
 
+    /// Creates a BMP280 driver with the specified address from calibration
+    /// saved earlier with [`calibration`](Self::calibration), without any
+    /// I2C transaction
+    pub fn from_calibration(i2c: I2C, addr: u8, cal: CalibrationData) -> BMP280<I2C> {
+        let mut chip = Self::uncalibrated(i2c, addr);
+        chip.apply_calibration(&cal);
+        chip
+    }
+
     /// Create a new BMP280 driver with the default address
     pub fn new<E>(i2c: I2C) -> Result<BMP280<I2C>, E>
     where
@@ -69,13 +280,131 @@ impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
     }
 }
 
-impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
+impl<I2C: ehal::i2c::I2c, MODE> BMP280<I2C, MODE> {
+    /// Switches to the chip at the specified address and reads its
+    /// calibration. The cached settings and the relative altitude reference
+    /// were the other chip's, so they are dropped. The user offsets are kept,
+    /// set them again if the new chip needs others. Returns whether a BMP280
+    /// answered there
+    pub fn set_address(&mut self, addr: u8) -> Result<bool, I2C::Error> {
+        check_address(addr);
+        self.addr = addr;
+        self.calibrated = false;
+        self.forget_settings();
+        self.reference_pa = None;
+        if self.id()? != CHIP_ID_BMP280 {
+            return Ok(false);
+        }
+
+        self.read_calibration();
+        Ok(true)
+    }
+
+    /// Splits every register read into a write of the register address and,
+    /// `split.gap_us` later, a separate read, rather than one transaction
+    /// with a repeated start. Some level shifters, isolators and long cables
+    /// slow the bus enough for the latter to fail now and then. `None` goes
+    /// back to single transactions
+    pub fn set_split_reads(&mut self, split: Option<SplitReads>) {
+        self.split_reads = split;
+    }
+
+    /// Adds `celsius` to every temperature read from now on, e.g. the error
+    /// measured against a reference thermometer. Rounded to a thousandth of
+    /// a degree, and only applied to the output: the pressure is still
+    /// compensated with the temperature the chip measured
+    pub fn set_temp_offset(&mut self, celsius: f64) {
+        self.temp_offset_mc = libm::round(celsius * 1000.0) as i32;
+    }
+
+    /// Adds `pa` to every pressure read from now on, rounded to a whole Pascal
+    pub fn set_pressure_offset(&mut self, pa: f64) {
+        self.pressure_offset_pa = libm::round(pa) as i32;
+    }
+
+    /// Returns the offset added to the temperature, in degrees Celsius
+    #[must_use]
+    pub fn temp_offset(&self) -> f64 {
+        self.temp_offset_mc as f64 / 1000.0
+    }
+
+    /// Returns the offset added to the pressure, in Pascals
+    #[must_use]
+    pub fn pressure_offset(&self) -> f64 {
+        self.pressure_offset_pa as f64
+    }
+
+    /// Returns whether the calibration coefficients were loaded, readings
+    /// are meaningless otherwise
+    #[must_use]
+    pub fn is_calibrated(&self) -> bool {
+        self.calibrated
+    }
+
+    /// Returns the calibration coefficients in register order, `None` if
+    /// they weren't loaded
+    #[must_use]
+    pub fn calibration(&self) -> Option<CalibrationData> {
+        self.calibrated.then(|| CalibrationData {
+            bytes: self.calibration_bytes(),
+        })
+    }
+
+    /// CRC-16/CCITT over the calibration coefficients, in register order
+    ///
+    /// Capture it after construction and compare it later, a mismatch means
+    /// the coefficients got corrupted and should be read again with
+    /// [`BMP280::set_address`]
+    #[must_use]
+    pub fn calibration_checksum(&self) -> u16 {
+        let mut crc: u16 = 0xFFFF;
+        for byte in self.calibration_bytes() {
+            crc ^= (byte as u16) << 8;
+            for _ in 0..8 {
+                crc = if crc & 0x8000 != 0 {
+                    (crc << 1) ^ 0x1021
+                } else {
+                    crc << 1
+                };
+            }
+        }
+        crc
+    }
+
+    fn calibration_bytes(&self) -> [u8; CalibrationData::LEN] {
+        let words = [
+            self.dig_t1,
+            self.dig_t2 as u16,
+            self.dig_t3 as u16,
+            self.dig_p1,
+            self.dig_p2 as u16,
+            self.dig_p3 as u16,
+            self.dig_p4 as u16,
+            self.dig_p5 as u16,
+            self.dig_p6 as u16,
+            self.dig_p7 as u16,
+            self.dig_p8 as u16,
+            self.dig_p9 as u16,
+        ];
+        let mut bytes = [0; CalibrationData::LEN];
+        for (chunk, word) in bytes.chunks_exact_mut(2).zip(words) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
     fn read_calibration(&mut self) {
-        let mut data: [u8; 24] = [0; 24];
-        let _ = self
-            .com
-            .write_read(self.addr, &[Register::calib00 as u8], &mut data);
+        let mut data: [u8; CalibrationData::LEN] = [0; CalibrationData::LEN];
+        if self.read_registers(Register::calib00, &mut data).is_err() {
+            self.calibrated = false;
+            return;
+        }
 
+        self.apply_calibration(&CalibrationData { bytes: data });
+    }
+
+    fn apply_calibration(&mut self, cal: &CalibrationData) {
+        let data = &cal.bytes;
         self.dig_t1 = ((data[1] as u16) << 8) | (data[0] as u16);
         self.dig_t2 = ((data[3] as i16) << 8) | (data[2] as i16);
         self.dig_t3 = ((data[5] as i16) << 8) | (data[4] as i16);
@@ -89,50 +418,220 @@ impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
         self.dig_p7 = ((data[19] as i16) << 8) | (data[18] as i16);
         self.dig_p8 = ((data[21] as i16) << 8) | (data[20] as i16);
         self.dig_p9 = ((data[23] as i16) << 8) | (data[22] as i16);
+        self.calibrated = true;
     }
 
     /// Reads and returns temperature
+    #[must_use]
     pub fn temp(&mut self) -> f64 {
         let mut data: [u8; 6] = [0, 0, 0, 0, 0, 0];
-        let _ = self
-            .com
-            .write_read(self.addr, &[Register::press as u8], &mut data);
+        let _ = self.read_registers(Register::press, &mut data);
         let temp = (data[3] as i32) << 12 | (data[4] as i32) << 4 | (data[5] as i32) >> 4;
 
-        let v1 = (((temp >> 3) - ((self.dig_t1 as i32) << 1)) * (self.dig_t2 as i32)) >> 11;
-        let v2 = (((((temp >> 4) - (self.dig_t1 as i32)) * ((temp >> 4) - (self.dig_t1 as i32)))
+        self.t_fine = self.fine_temperature(temp);
+        compensate_temperature(self.t_fine) + self.temp_offset()
+    }
+
+    /// Reads and returns pressure, compensated with the temperature
+    /// from the last call to [`temp`](Self::temp)
+    ///
+    /// Without a preceding `temp()` the result is meaningless, and with one
+    /// the two may come from different conversions. Use
+    /// [`measure`](Self::measure) to get both right
+    #[must_use]
+    pub fn pressure(&mut self) -> f64 {
+        let mut data: [u8; 6] = [0, 0, 0, 0, 0, 0];
+        let _ = self.read_registers(Register::press, &mut data);
+        let press = (data[0] as i32) << 12 | (data[1] as i32) << 4 | (data[2] as i32) >> 4;
+
+        self.compensate_pressure(press, self.t_fine) + self.pressure_offset()
+    }
+
+    /// Reads temperature and pressure from the same conversion, the pressure
+    /// being compensated with that temperature
+    pub fn measure(&mut self) -> Result<Measurement, I2C::Error> {
+        self.measure_into(&mut [0; 6])
+    }
+
+    /// Like [`measure`](Self::measure), burst reading into `buf` rather than
+    /// a buffer on the stack
+    pub fn measure_into(&mut self, buf: &mut [u8; 6]) -> Result<Measurement, I2C::Error> {
+        let (press, temp) = self.read_raw_into(buf)?;
+        // The pressure compensation depends on it, so it goes first
+        self.t_fine = self.fine_temperature(temp);
+
+        Ok(Measurement {
+            temperature_c: compensate_temperature(self.t_fine) + self.temp_offset(),
+            pressure_pa: self.compensate_pressure(press, self.t_fine) + self.pressure_offset(),
+        })
+    }
+
+    /// Like [`measure`](Self::measure), also returning the data registers as
+    /// read, `press_msb` to `temp_xlsb`, to tell bus garbage from a
+    /// compensation error
+    pub fn measure_with_raw(&mut self) -> Result<(Measurement, [u8; 6]), I2C::Error> {
+        let mut raw = [0; 6];
+        let measurement = self.measure_into(&mut raw)?;
+        Ok((measurement, raw))
+    }
+
+    /// Like [`measure`](Self::measure), tagging the reading with `now`,
+    /// e.g. an `embassy_time::Instant`
+    pub fn measure_at<T>(&mut self, now: T) -> Result<TimedMeasurement<T>, I2C::Error> {
+        Ok(TimedMeasurement {
+            measurement: self.measure()?,
+            timestamp: now,
+        })
+    }
+
+    /// Reads and returns temperature in thousandths of a degree Celsius,
+    /// using integer arithmetic only
+    pub fn temp_millicelsius(&mut self) -> Result<i32, I2C::Error> {
+        let (_, temp) = self.read_raw()?;
+        let temp = (self.fine_temperature(temp) as i64 * 5 + 128) >> 8;
+
+        Ok(((temp * 10) as i32).saturating_add(self.temp_offset_mc))
+    }
+
+    /// Reads and returns pressure in Pascals, using integer arithmetic only.
+    /// `None` if the compensation overflows or divides by zero, which only
+    /// garbage calibration or readings make it do
+    pub fn pressure_pa_int(&mut self) -> Result<Option<u32>, I2C::Error> {
+        let (press, temp) = self.read_raw()?;
+        let pressure = self.compensate_pressure_int(press, self.fine_temperature(temp));
+
+        Ok(pressure.map(|pressure| pressure.saturating_add_signed(self.pressure_offset_pa)))
+    }
+
+    /// The datasheet's 64 bit compensation, with the shifts left written as
+    /// multiplications so that they are checked too
+    fn compensate_pressure_int(&self, press: i32, t_fine: i32) -> Option<u32> {
+        let v1 = t_fine as i64 - 128000;
+        let v2 = v1.checked_mul(v1)?.checked_mul(self.dig_p6 as i64)?;
+        let v2 = v2.checked_add(v1.checked_mul(self.dig_p5 as i64)?.checked_mul(1 << 17)?)?;
+        let v2 = v2.checked_add((self.dig_p4 as i64).checked_mul(1 << 35)?)?;
+        let v1 = (v1.checked_mul(v1)?.checked_mul(self.dig_p3 as i64)? >> 8)
+            .checked_add(v1.checked_mul(self.dig_p2 as i64)?.checked_mul(1 << 12)?)?;
+        let v1 = (1i64 << 47)
+            .checked_add(v1)?
+            .checked_mul(self.dig_p1 as i64)?
+            >> 33;
+
+        let p = 1048576 - press as i64;
+        let p = p
+            .checked_mul(1 << 31)?
+            .checked_sub(v2)?
+            .checked_mul(3125)?
+            .checked_div(v1)?;
+        let v1 = (self.dig_p9 as i64)
+            .checked_mul(p >> 13)?
+            .checked_mul(p >> 13)?
+            >> 25;
+        let v2 = (self.dig_p8 as i64).checked_mul(p)? >> 19;
+        // Q24.8 format
+        let p =
+            (p.checked_add(v1)?.checked_add(v2)? >> 8).checked_add((self.dig_p7 as i64) << 4)?;
+
+        u32::try_from(p >> 8).ok()
+    }
+
+    /// Whether a conversion is running
+    fn is_measuring(&mut self) -> Result<bool, I2C::Error> {
+        Ok(self.read_byte(Register::status)? & STATUS_MEASURING_MASK != 0)
+    }
+
+    /// Returns altitude in meters, given the current sea level pressure
+    /// in Pascals
+    pub fn altitude(&mut self, sea_level_pa: f64) -> Result<f64, I2C::Error> {
+        let pressure = self.measure()?.pressure_pa;
+        Ok(altitude_from_pressure(pressure, sea_level_pa))
+    }
+
+    /// Returns the meters above (or below) the point where
+    /// [`capture_reference`](Self::capture_reference) was called, `None`
+    /// until it was, without reading the chip
+    pub fn relative_altitude(&mut self) -> Result<Option<f64>, I2C::Error> {
+        let Some(reference) = self.reference_pa else {
+            return Ok(None);
+        };
+        let pressure = self.measure()?.pressure_pa;
+
+        Ok(Some(altitude_from_pressure(pressure, reference)))
+    }
+
+    /// Like [`altitude`](Self::altitude), with the altimeter setting (QNH)
+    /// in inches of mercury
+    pub fn altitude_from_qnh_inhg(&mut self, qnh_inhg: f64) -> Result<f64, I2C::Error> {
+        let pressure = self.measure()?.pressure_pa;
+        Ok(altitude_from_pressure(pressure, qnh_inhg * PA_PER_INHG))
+    }
+
+    /// Returns the density altitude in meters, the altitude in the standard
+    /// atmosphere where the air is as dense as the (dry) air measured here.
+    /// Unlike [`altitude`](Self::altitude) it needs no sea level pressure
+    pub fn density_altitude(&mut self) -> Result<f64, I2C::Error> {
+        let measurement = self.measure()?;
+        let density =
+            measurement.pressure_pa / (R_DRY_AIR * celsius_to_kelvin(measurement.temperature_c));
+
+        Ok(44330.8 * (1.0 - libm::pow(density / ISA_SEA_LEVEL_DENSITY, 0.234969)))
+    }
+
+    /// Burst reads the raw pressure and temperature
+    fn read_raw(&mut self) -> Result<(i32, i32), I2C::Error> {
+        self.read_raw_into(&mut [0; 6])
+    }
+
+    /// Burst reads the raw pressure and temperature into `data`
+    fn read_raw_into(&mut self, data: &mut [u8; 6]) -> Result<(i32, i32), I2C::Error> {
+        self.read_registers(Register::press, data)?;
+        let press = (data[0] as i32) << 12 | (data[1] as i32) << 4 | (data[2] as i32) >> 4;
+        let temp = (data[3] as i32) << 12 | (data[4] as i32) << 4 | (data[5] as i32) >> 4;
+
+        Ok((press, temp))
+    }
+
+    /// Fine temperature shared by the temperature and pressure compensation
+    fn fine_temperature(&self, temp: i32) -> i32 {
+        // Wide enough for any 20 bit reading and calibration
+        let temp = temp as i64;
+        let v1 = (((temp >> 3) - ((self.dig_t1 as i64) << 1)) * (self.dig_t2 as i64)) >> 11;
+        let v2 = (((((temp >> 4) - (self.dig_t1 as i64)) * ((temp >> 4) - (self.dig_t1 as i64)))
             >> 12)
-            * (self.dig_t3 as i32))
+            * (self.dig_t3 as i64))
             >> 14;
 
-        let temp = ((v1 + v2) * 5 + 128) >> 8;
+        // Only out of range with garbage calibration, saturate rather than
+        // wrap around to a temperature of the opposite sign
+        (v1 + v2).clamp(i32::MIN.into(), i32::MAX.into()) as i32
+    }
+
+    fn compensate_pressure(&self, press: i32, t_fine: i32) -> f64 {
+        let mut v1 = (t_fine as f64) / 2.0 - 64000.0;
+        let mut v2 = v1 * v1 * (self.dig_p6 as f64) / 32768.0;
+        v2 += v1 * (self.dig_p5 as f64) * 2.0;
+        v2 = (v2 / 4.0) + (self.dig_p4 as f64) * 65536.0;
+        v1 = ((self.dig_p3 as f64) * v1 * v1 / 524288.0 + (self.dig_p2 as f64) * v1) / 524288.0;
+        v1 = (1.0 + v1 / 32768.0) * (self.dig_p1 as f64);
+        if v1 == 0.0 {
+            // Avoid a division by zero
+            return 0.0;
+        }
+
+        let p = 1048576.0 - press as f64;
+        let p = (p - (v2 / 4096.0)) * 6250.0 / v1;
+        let v1 = (self.dig_p9 as f64) * p * p / 2147483648.0;
+        let v2 = p * (self.dig_p8 as f64) / 32768.0;
 
-        temp as f64 / 100.0
+        p + (v1 + v2 + (self.dig_p7 as f64)) / 16.0
     }
 
     /// Returns current config
-    pub fn config(&mut self) -> Config {
-        let config = self.read_byte(Register::config);
-        let t_sb = match (config & (0b111 << 5)) >> 5 {
-            x if x == Standby::ms0_5 as u8 => Standby::ms0_5,
-            x if x == Standby::ms62_5 as u8 => Standby::ms62_5,
-            x if x == Standby::ms125 as u8 => Standby::ms125,
-            x if x == Standby::ms250 as u8 => Standby::ms250,
-            x if x == Standby::ms500 as u8 => Standby::ms500,
-            x if x == Standby::ms1000 as u8 => Standby::ms1000,
-            x if x == Standby::ms2000 as u8 => Standby::ms2000,
-            x if x == Standby::ms4000 as u8 => Standby::ms4000,
-            _ => Standby::unknown,
-        };
-        let filter = match (config & (0b111 << 2)) >> 2 {
-            x if x == Filter::off as u8 => Filter::off,
-            x if x == Filter::c2 as u8 => Filter::c2,
-            x if x == Filter::c4 as u8 => Filter::c4,
-            x if x == Filter::c8 as u8 => Filter::c8,
-            x if x == Filter::c16 as u8 => Filter::c16,
-            _ => Filter::unknown,
-        };
-        Config { t_sb, filter }
+    pub fn config(&mut self) -> Result<Config, I2C::Error> {
+        let config = self.read_byte(Register::config)?;
+        let t_sb = Standby::try_from((config >> 5) & 0b111).unwrap_or(Standby::unknown);
+        let filter = Filter::try_from((config >> 2) & 0b111).unwrap_or(Filter::unknown);
+        Ok(Config { t_sb, filter })
     }
 
     /// Sets configuration
@@ -140,84 +639,755 @@ impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
         let config: u8 = 0x00;
         let t_sb = (new.t_sb as u8) << 5;
         let filter = (new.filter as u8) << 2;
-        self.write_byte(Register::config, config | t_sb | filter);
+        let config = config | t_sb | filter;
+        if self.write_byte(Register::config, config).is_ok() {
+            self.config = Some(new);
+        }
     }
 
-    /// Sets control
-    pub fn set_control(&mut self, new: Control) {
+    /// Returns the config last set, without reading the register. `None`
+    /// until one is set, and again after a reset
+    #[must_use]
+    pub fn cached_config(&self) -> Option<Config> {
+        self.config
+    }
+
+    fn write_control(&mut self, new: Control) -> Result<(), I2C::Error> {
         let osrs_t: u8 = (new.osrs_t as u8) << 5;
         let osrs_p: u8 = (new.osrs_p as u8) << 2;
         let control: u8 = osrs_t | osrs_p | (new.mode as u8);
-        self.write_byte(Register::ctrl_meas, control);
+        self.write_byte(Register::ctrl_meas, control)?;
+        self.control = Some(new);
+        Ok(())
+    }
+
+    /// Returns the control last set, without reading the register. `None`
+    /// until one is set, and again after a reset.
+    ///
+    /// The mode is the one set, not a forced conversion started by
+    /// [`measure_forced`](Self::measure_forced) nor the sleep mode the chip
+    /// goes back to afterwards
+    #[must_use]
+    pub fn cached_control(&self) -> Option<Control> {
+        self.control
     }
 
     /// Returns control
-    pub fn control(&mut self) -> Control {
-        let config = self.read_byte(Register::ctrl_meas);
-        let osrs_t = match (config & (0b111 << 5)) >> 5 {
-            x if x == Oversampling::skipped as u8 => Oversampling::skipped,
-            x if x == Oversampling::x1 as u8 => Oversampling::x1,
-            x if x == Oversampling::x2 as u8 => Oversampling::x2,
-            x if x == Oversampling::x4 as u8 => Oversampling::x4,
-            x if x == Oversampling::x8 as u8 => Oversampling::x8,
-            _ => Oversampling::x16,
-        };
-        let osrs_p = match (config & (0b111 << 2)) >> 2 {
-            x if x == Oversampling::skipped as u8 => Oversampling::skipped,
-            x if x == Oversampling::x1 as u8 => Oversampling::x1,
-            x if x == Oversampling::x2 as u8 => Oversampling::x2,
-            x if x == Oversampling::x4 as u8 => Oversampling::x4,
-            x if x == Oversampling::x8 as u8 => Oversampling::x8,
-            _ => Oversampling::x16,
-        };
-        let mode = match config & 0b11 {
-            x if x == PowerMode::Sleep as u8 => PowerMode::Sleep,
-            x if x == PowerMode::Forced as u8 => PowerMode::Forced,
-            x if x == PowerMode::Normal as u8 => PowerMode::Normal,
-            _ => PowerMode::Forced,
-        };
+    pub fn control(&mut self) -> Result<Control, I2C::Error> {
+        let config = self.read_byte(Register::ctrl_meas)?;
+        // Every value of the masked bits decodes
+        let osrs_t = Oversampling::try_from((config >> 5) & 0b111).unwrap_or(Oversampling::x16);
+        let osrs_p = Oversampling::try_from((config >> 2) & 0b111).unwrap_or(Oversampling::x16);
+        let mode = PowerMode::try_from(config & 0b11).unwrap_or(PowerMode::Forced);
 
-        Control {
+        Ok(Control {
             osrs_t,
             osrs_p,
             mode,
+        })
+    }
+
+    fn set_mode(&mut self, mode: PowerMode) -> Result<(), I2C::Error> {
+        // Only the mode changes, so write back the oversampling set last, or
+        // the one in the register if none was, never a guess
+        let control = match self.control {
+            Some(control) => control,
+            None => self.control()?,
+        };
+        self.write_control(Control { mode, ..control })
+    }
+
+    /// Puts the chip into normal mode, keeping the oversampling settings, and
+    /// restricts the driver to the normal mode API
+    pub fn into_normal(mut self) -> Result<BMP280<I2C, mode::Normal>, I2C::Error> {
+        self.set_mode(PowerMode::Normal)?;
+        Ok(self.into_mode())
+    }
+
+    /// Puts the chip to sleep, keeping the oversampling settings, and
+    /// restricts the driver to the forced mode API
+    pub fn into_forced(mut self) -> Result<BMP280<I2C, mode::Forced>, I2C::Error> {
+        self.set_mode(PowerMode::Sleep)?;
+        Ok(self.into_mode())
+    }
+
+    /// Lifts the restriction to one power mode, leaving the chip as it is
+    pub fn into_any_mode(self) -> BMP280<I2C> {
+        self.into_mode()
+    }
+
+    fn into_mode<M>(self) -> BMP280<I2C, M> {
+        BMP280 {
+            com: self.com,
+            addr: self.addr,
+            dig_t1: self.dig_t1,
+            dig_t2: self.dig_t2,
+            dig_t3: self.dig_t3,
+            t_fine: self.t_fine,
+            dig_p1: self.dig_p1,
+            dig_p2: self.dig_p2,
+            dig_p3: self.dig_p3,
+            dig_p4: self.dig_p4,
+            dig_p5: self.dig_p5,
+            dig_p6: self.dig_p6,
+            dig_p7: self.dig_p7,
+            dig_p8: self.dig_p8,
+            dig_p9: self.dig_p9,
+            calibrated: self.calibrated,
+            reference_pa: self.reference_pa,
+            split_reads: self.split_reads,
+            control: self.control,
+            config: self.config,
+            temp_offset_mc: self.temp_offset_mc,
+            pressure_offset_pa: self.pressure_offset_pa,
+            mode: PhantomData,
         }
     }
 
-    /// Returns device status
-    pub fn status(&mut self) -> Status {
-        let status = self.read_byte(Register::status);
-        Status {
-            measuring: 0 != (status & 0b00001000),
-            im_update: 0 != (status & 0b00000001),
+    /// Polls the status every 100 µs until the NVM copy finishes, for at
+    /// most `max_wait_us`. Returns whether the chip became ready in time
+    pub fn wait_until_ready(
+        &mut self,
+        delay: &mut impl ehal::delay::DelayNs,
+        max_wait_us: u32,
+    ) -> Result<bool, I2C::Error> {
+        let mut waited_us = 0;
+        while self.read_byte(Register::status)? & STATUS_IM_UPDATE_MASK != 0 {
+            if waited_us >= max_wait_us {
+                return Ok(false);
+            }
+            delay.delay_us(100);
+            waited_us += 100;
         }
+
+        Ok(true)
+    }
+
+    /// Returns device status
+    pub fn status(&mut self) -> Result<Status, I2C::Error> {
+        let status = self.read_byte(Register::status)?;
+        Ok(Status {
+            measuring: 0 != (status & STATUS_MEASURING_MASK),
+            im_update: 0 != (status & STATUS_IM_UPDATE_MASK),
+        })
     }
 
     /// Returns device id
-    pub fn id(&mut self) -> u8 {
+    pub fn id(&mut self) -> Result<u8, I2C::Error> {
         self.read_byte(Register::id)
     }
 
-    /// Software reset, emulates POR
-    pub fn reset(&mut self) {
-        self.write_byte(Register::reset, 0xB6); // Magic from documentation
+    /// Drops the cached settings, once they no longer match the registers
+    fn forget_settings(&mut self) {
+        self.control = None;
+        self.config = None;
     }
 
-    fn write_byte(&mut self, reg: Register, byte: u8) {
+    fn write_byte(&mut self, reg: Register, byte: u8) -> Result<(), I2C::Error> {
+        if self.split_reads.is_some() {
+            return self.com.write(self.addr, &[reg as u8, byte]);
+        }
+
         let mut buffer = [0];
-        let _ = self
-            .com
-            .write_read(self.addr, &[reg as u8, byte], &mut buffer);
+        self.com
+            .write_read(self.addr, &[reg as u8, byte], &mut buffer)
     }
 
-    fn read_byte(&mut self, reg: Register) -> u8 {
+    fn read_byte(&mut self, reg: Register) -> Result<u8, I2C::Error> {
         let mut data: [u8; 1] = [0];
-        let _ = self.com.write_read(self.addr, &[reg as u8], &mut data);
-        data[0]
+        self.read_registers(reg, &mut data)?;
+        Ok(data[0])
+    }
+
+    /// Reads consecutive registers from `start` on
+    fn read_registers(&mut self, start: Register, data: &mut [u8]) -> Result<(), I2C::Error> {
+        match self.split_reads {
+            // Selecting the register and reading it in one transaction keeps
+            // other devices on a shared bus from moving the register pointer
+            // between the two
+            None => self.com.transaction(
+                self.addr,
+                &mut [Operation::Write(&[start as u8]), Operation::Read(data)],
+            ),
+            Some(split) => {
+                self.com.write(self.addr, &[start as u8])?;
+                (split.delay_us)(split.gap_us);
+                self.com.read(self.addr, data)
+            }
+        }
+    }
+}
+
+impl<I2C: ehal::i2c::I2c, MODE: mode::Mode> BMP280<I2C, MODE> {
+    /// Sets control. A driver restricted to one power mode stays in it: the
+    /// mode of `new` becomes normal for [`mode::Normal`], and normal becomes
+    /// sleep for [`mode::Forced`]
+    pub fn set_control(&mut self, new: Control) {
+        let _ = self.write_control(Control {
+            mode: MODE::restrict(new.mode),
+            ..new
+        });
+    }
+
+    /// Applies one of the datasheet recommended settings, its power mode
+    /// restricted as by [`set_control`](Self::set_control)
+    pub fn apply_preset(&mut self, preset: Preset) {
+        let (control, config) = preset.settings();
+        self.apply_settings(control, config);
+    }
+
+    fn apply_settings(&mut self, control: Control, config: Config) {
+        // Config writes may be ignored in normal mode, so go to sleep first
+        let _ = self.write_control(Control {
+            mode: PowerMode::Sleep,
+            ..control
+        });
+        self.set_config(config);
+        self.set_control(control);
+    }
+
+    /// Soft resets the chip, then once it is ready reads the calibration
+    /// again and applies the default [`Control`] and [`Config`], the power
+    /// mode restricted as by [`set_control`](Self::set_control). Returns
+    /// whether the chip came back calibrated
+    pub fn reset_to_defaults(
+        &mut self,
+        delay: &mut impl ehal::delay::DelayNs,
+    ) -> Result<bool, I2C::Error> {
+        self.write_byte(Register::reset, RESET_MAGIC)?;
+        self.forget_settings();
+        delay.delay_us(STARTUP_TIME_US);
+        if !self.wait_until_ready(delay, STARTUP_TIME_US)? {
+            return Ok(false);
+        }
+
+        self.read_calibration();
+        self.apply_settings(Control::default(), Config::default());
+        Ok(self.calibrated)
+    }
+}
+
+impl<I2C: ehal::i2c::I2c> BMP280<I2C> {
+    /// Puts the chip into sleep mode, keeping the oversampling settings.
+    ///
+    /// Call this before dropping the driver, otherwise a chip left in normal
+    /// mode keeps sampling.
+    pub fn power_down(&mut self) -> Result<(), I2C::Error> {
+        self.set_mode(PowerMode::Sleep)
+    }
+
+    /// Wakes the chip up into normal mode, keeping the oversampling settings
+    pub fn power_up(&mut self) -> Result<(), I2C::Error> {
+        self.set_mode(PowerMode::Normal)
     }
 }
 
+impl<I2C: ehal::i2c::I2c> BMP280<I2C, mode::Normal> {
+    /// Puts the chip into sleep mode, keeping the oversampling settings, the
+    /// driver becoming a forced mode one, see [`into_forced`](Self::into_forced)
+    pub fn power_down(self) -> Result<BMP280<I2C, mode::Forced>, I2C::Error> {
+        self.into_forced()
+    }
+}
+
+impl<I2C: ehal::i2c::I2c> BMP280<I2C, mode::Forced> {
+    /// Wakes the chip up into normal mode, keeping the oversampling settings,
+    /// the driver becoming a normal mode one, see
+    /// [`into_normal`](Self::into_normal)
+    pub fn power_up(self) -> Result<BMP280<I2C, mode::Normal>, I2C::Error> {
+        self.into_normal()
+    }
+}
+
+impl<I2C: ehal::i2c::I2c, MODE: mode::AllowsForced> BMP280<I2C, MODE> {
+    /// Software reset, emulates POR. The chip comes back asleep, which is
+    /// why only drivers allowing the forced mode have it
+    pub fn reset(&mut self) {
+        let _ = self.write_byte(Register::reset, RESET_MAGIC);
+        self.forget_settings();
+    }
+
+    /// Triggers a forced mode conversion with the current oversampling
+    /// settings and reads it once done, like [`measure`](Self::measure)
+    pub fn measure_forced(
+        &mut self,
+        delay: &mut impl ehal::delay::DelayNs,
+    ) -> Result<Measurement, I2C::Error> {
+        // Never pending, the delay blocking instead
+        embassy_futures::block_on(self.measure_forced_async(&mut BlockingDelay(delay)))
+    }
+
+    /// Like [`measure_forced`](Self::measure_forced), waiting with an async
+    /// delay so other tasks run during the conversion
+    pub async fn measure_forced_async(
+        &mut self,
+        delay: &mut impl embedded_hal_async::delay::DelayNs,
+    ) -> Result<Measurement, I2C::Error> {
+        let wait_us = self.start_forced()?;
+        delay.delay_us(wait_us).await;
+        while self.is_measuring()? {
+            delay.delay_us(CONVERSION_POLL_US).await;
+        }
+
+        self.measure()
+    }
+
+    /// Triggers a forced mode conversion, returning how long it takes at
+    /// most in µs
+    fn start_forced(&mut self) -> Result<u32, I2C::Error> {
+        let control = self.read_byte(Register::ctrl_meas)?;
+        self.write_byte(
+            Register::ctrl_meas,
+            (control & !0b11) | PowerMode::Forced as u8,
+        )?;
+
+        // Maximum measurement time from the datasheet, section 3.8.1
+        let osrs_t = samples((control >> 5) & 0b111);
+        let osrs_p = samples((control >> 2) & 0b111);
+        let mut wait_us = 1250 + 2300 * osrs_t;
+        if osrs_p > 0 {
+            wait_us += 2300 * osrs_p + 575;
+        }
+
+        Ok(wait_us)
+    }
+
+    /// Takes `n` (at least one) forced readings and returns their mean
+    pub fn measure_averaged(
+        &mut self,
+        n: u8,
+        delay: &mut impl ehal::delay::DelayNs,
+    ) -> Result<Measurement, I2C::Error> {
+        let n = n.max(1);
+        let mut temperature_c = 0.0;
+        let mut pressure_pa = 0.0;
+        for _ in 0..n {
+            let measurement = self.measure_forced(delay)?;
+            temperature_c += measurement.temperature_c;
+            pressure_pa += measurement.pressure_pa;
+        }
+
+        Ok(Measurement {
+            temperature_c: temperature_c / n as f64,
+            pressure_pa: pressure_pa / n as f64,
+        })
+    }
+
+    /// Endless forced readings, `interval_ms` apart (the first one is taken
+    /// right away)
+    pub fn iter_measurements<'a, D: ehal::delay::DelayNs>(
+        &'a mut self,
+        delay: &'a mut D,
+        interval_ms: u32,
+    ) -> Measurements<'a, I2C, D, MODE> {
+        Measurements {
+            chip: self,
+            delay,
+            interval_ms,
+            first: true,
+        }
+    }
+
+    /// Averages a few forced readings into the ground level pressure used
+    /// by [`relative_altitude`](Self::relative_altitude), and returns it
+    pub fn capture_reference(
+        &mut self,
+        delay: &mut impl ehal::delay::DelayNs,
+    ) -> Result<f64, I2C::Error> {
+        let reference = self.measure_averaged(REFERENCE_SAMPLES, delay)?.pressure_pa;
+        self.reference_pa = Some(reference);
+
+        Ok(reference)
+    }
+}
+
+/// Blocking delay behind the async delay trait, so that the forced
+/// measurement is written once for both
+struct BlockingDelay<'a, D>(&'a mut D);
+
+impl<D: ehal::delay::DelayNs> embedded_hal_async::delay::DelayNs for BlockingDelay<'_, D> {
+    async fn delay_ns(&mut self, ns: u32) {
+        self.0.delay_ns(ns);
+    }
+
+    async fn delay_us(&mut self, us: u32) {
+        self.0.delay_us(us);
+    }
+
+    async fn delay_ms(&mut self, ms: u32) {
+        self.0.delay_ms(ms);
+    }
+}
+
+impl<I2C: ehal::i2c::I2c, MODE: mode::AllowsNormal> BMP280<I2C, MODE> {
+    /// Waits for the end of the next conversion in normal mode, polling the
+    /// `measuring` status bit every 500 µs for at most `max_wait_us`, so
+    /// fresh data can be read right after it returns. Returns whether a
+    /// conversion ended in time, `false` right away if the chip isn't in
+    /// normal mode
+    pub async fn wait_for_data_ready(
+        &mut self,
+        delay: &mut impl embedded_hal_async::delay::DelayNs,
+        max_wait_us: u32,
+    ) -> Result<bool, I2C::Error> {
+        let control = self.read_byte(Register::ctrl_meas)?;
+        if control & 0b11 != PowerMode::Normal as u8 {
+            return Ok(false);
+        }
+
+        // Wait for a conversion to start, unless one is running already, then
+        // for it to end
+        let mut waited_us = 0;
+        for measuring in [false, true] {
+            while self.is_measuring()? == measuring {
+                if waited_us >= max_wait_us {
+                    return Ok(false);
+                }
+                delay.delay_us(CONVERSION_POLL_US).await;
+                waited_us += CONVERSION_POLL_US;
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+/// Number of samples taken for the oversampling register bits
+fn samples(bits: u8) -> u32 {
+    match bits {
+        0 => 0,
+        1..=5 => 1 << (bits - 1),
+        _ => 16,
+    }
+}
+
+/// Catches addresses the chip can't have, typically the 8-bit write address
+/// (0xEC/0xEE) passed instead of the 7-bit one
+fn check_address(addr: u8) {
+    debug_assert!(
+        addr == 0x76 || addr == 0x77,
+        "invalid BMP280 address {:#04x}, expected 0x76 or 0x77",
+        addr
+    );
+}
+
+/// Converts a pressure from Pascals to inches of mercury
+pub fn pascals_to_inhg(pa: f64) -> f64 {
+    pa / PA_PER_INHG
+}
+
+/// Converts a pressure from Pascals to millimeters of mercury
+pub fn pascals_to_mmhg(pa: f64) -> f64 {
+    pa / PA_PER_MMHG
+}
+
+/// Converts a temperature from degrees Celsius to Fahrenheit
+pub fn celsius_to_fahrenheit(celsius: f64) -> f64 {
+    celsius * 9.0 / 5.0 + 32.0
+}
+
+/// Returns the altitude in meters at which `pressure_pa` is measured, for
+/// the pressure `sea_level_pa` at sea level, e.g. from an earlier
+/// [`measure`](BMP280::measure) without reading the sensor again
+pub fn altitude_from_pressure(pressure_pa: f64, sea_level_pa: f64) -> f64 {
+    // International barometric formula
+    44330.0 * (1.0 - libm::pow(pressure_pa / sea_level_pa, 1.0 / 5.255))
+}
+
+/// Converts a temperature from degrees Celsius to Kelvin
+pub fn celsius_to_kelvin(celsius: f64) -> f64 {
+    celsius + 273.15
+}
+
+/// Typical RMS noise of the pressure, in Pascals, with the given pressure
+/// oversampling and IIR filter, `None` if pressure is skipped or the filter
+/// unknown
+pub fn pressure_noise_pa(osrs_p: Oversampling, filter: Filter) -> Option<f64> {
+    let row = match osrs_p {
+        Oversampling::skipped => return None,
+        Oversampling::x1 => 0,
+        Oversampling::x2 => 1,
+        Oversampling::x4 => 2,
+        Oversampling::x8 => 3,
+        Oversampling::x16 => 4,
+    };
+    let column = match filter {
+        Filter::off => 0,
+        Filter::c2 => 1,
+        Filter::c4 => 2,
+        Filter::c8 => 3,
+        Filter::c16 => 4,
+        Filter::unknown => return None,
+    };
+
+    Some(PRESSURE_NOISE_PA[row][column])
+}
+
+/// [`pressure_noise_pa`] as a standard deviation of the altitude near sea
+/// level, in meters
+pub fn altitude_stddev(osrs_p: Oversampling, filter: Filter) -> Option<f64> {
+    pressure_noise_pa(osrs_p, filter).map(|pa| pa * METERS_PER_PA)
+}
+
+fn compensate_temperature(t_fine: i32) -> f64 {
+    let temp = (t_fine as i64 * 5 + 128) >> 8;
+    temp as f64 / 100.0
+}
+
+/// Register reads split into a write and a read, see
+/// [`BMP280::set_split_reads`]
+#[derive(Debug, Copy, Clone)]
+pub struct SplitReads {
+    /// Time between the write and the read, in µs
+    pub gap_us: u32,
+    /// Waits for the given µs, e.g.
+    /// `|us| embassy_time::block_for(Duration::from_micros(us.into()))`
+    pub delay_us: fn(u32),
+}
+
+/// Trimming parameters as read from the chip, e.g. to be persisted and
+/// handed to [`BMP280::from_calibration`] on the next boot
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct CalibrationData {
+    bytes: [u8; CalibrationData::LEN],
+}
+
+impl CalibrationData {
+    /// Size of the calibration registers
+    pub const LEN: usize = 24;
+
+    /// Wraps the calibration registers, `dig_T1` to `dig_P9` little endian
+    pub fn from_bytes(bytes: [u8; Self::LEN]) -> CalibrationData {
+        CalibrationData { bytes }
+    }
+
+    /// Like [`from_bytes`](Self::from_bytes), `None` unless `slice` is
+    /// exactly [`LEN`](Self::LEN) bytes long
+    pub fn from_slice(slice: &[u8]) -> Option<CalibrationData> {
+        slice.try_into().ok().map(Self::from_bytes)
+    }
+
+    /// Returns the calibration registers
+    pub fn to_bytes(&self) -> [u8; Self::LEN] {
+        self.bytes
+    }
+}
+
+/// Compensated reading
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct Measurement {
+    /// Temperature in degrees Celsius
+    pub temperature_c: f64,
+    /// Pressure in Pascals
+    pub pressure_pa: f64,
+}
+
+impl Measurement {
+    /// Returns whether the temperature is above `celsius`
+    pub fn exceeds_temp(&self, celsius: f64) -> bool {
+        self.temperature_c > celsius
+    }
+
+    /// Returns whether the temperature is between `min` and `max` degrees
+    /// Celsius, inclusive
+    pub fn within(&self, min: f64, max: f64) -> bool {
+        (min..=max).contains(&self.temperature_c)
+    }
+
+    /// Packs the reading into 8 bytes: the temperature in hundredths of a
+    /// degree as an `i32`, then the pressure in whole Pascals as a `u32`, both
+    /// little endian
+    pub fn to_le_bytes(&self) -> [u8; 8] {
+        let temp_centi = libm::round(self.temperature_c * 100.0) as i32;
+        let pressure_pa = libm::round(self.pressure_pa) as u32;
+
+        let mut bytes = [0; 8];
+        bytes[..4].copy_from_slice(&temp_centi.to_le_bytes());
+        bytes[4..].copy_from_slice(&pressure_pa.to_le_bytes());
+        bytes
+    }
+
+    /// Unpacks a reading written by [`to_le_bytes`](Self::to_le_bytes)
+    pub fn from_le_bytes(bytes: [u8; 8]) -> Measurement {
+        let temp_centi = i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let pressure_pa = u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
+
+        Measurement {
+            temperature_c: temp_centi as f64 / 100.0,
+            pressure_pa: pressure_pa as f64,
+        }
+    }
+}
+
+/// Bound of a [`BoundsWatcher`] a reading fell outside of
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
+pub enum Alert {
+    /// Temperature below the range
+    TemperatureLow,
+    /// Temperature above the range
+    TemperatureHigh,
+    /// Pressure below the range
+    PressureLow,
+    /// Pressure above the range
+    PressureHigh,
+}
+
+/// Accepted ranges of both channels, telling which one a reading leaves.
+/// An infinite bound leaves that side unchecked:
+///
+/// ```
+/// # use embedded_nov_2024::bmp280::*;
+/// let watcher = BoundsWatcher {
+///     temp_range: 18.0..=26.0,
+///     pressure_range: f64::NEG_INFINITY..=f64::INFINITY,
+/// };
+/// let measurement = Measurement {
+///     temperature_c: 27.5,
+///     pressure_pa: 101_325.0,
+/// };
+/// assert_eq!(watcher.check(&measurement), Some(Alert::TemperatureHigh));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoundsWatcher {
+    /// Temperatures in degrees Celsius, inclusive
+    pub temp_range: RangeInclusive<f64>,
+    /// Pressures in Pascals, inclusive
+    pub pressure_range: RangeInclusive<f64>,
+}
+
+impl BoundsWatcher {
+    /// Returns the bound `m` violates, the temperature taking precedence
+    /// when both channels are out of range
+    #[must_use]
+    pub fn check(&self, m: &Measurement) -> Option<Alert> {
+        let t = m.temperature_c;
+        let p = m.pressure_pa;
+        if t < *self.temp_range.start() {
+            Some(Alert::TemperatureLow)
+        } else if t > *self.temp_range.end() {
+            Some(Alert::TemperatureHigh)
+        } else if p < *self.pressure_range.start() {
+            Some(Alert::PressureLow)
+        } else if p > *self.pressure_range.end() {
+            Some(Alert::PressureHigh)
+        } else {
+            None
+        }
+    }
+}
+
+/// Running minimum, maximum and mean of both channels over the measurements
+/// it was updated with, e.g. for a daily high/low summary
+#[derive(Debug, Copy, Clone, PartialEq, Default)]
+pub struct SessionStats {
+    count: u32,
+    temp_min: f64,
+    temp_max: f64,
+    temp_sum: f64,
+    pressure_min: f64,
+    pressure_max: f64,
+    pressure_sum: f64,
+}
+
+impl SessionStats {
+    /// Creates an accumulator with no measurements yet
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accounts for the measurement `m`
+    pub fn update(&mut self, m: &Measurement) {
+        if self.count == 0 {
+            self.temp_min = m.temperature_c;
+            self.temp_max = m.temperature_c;
+            self.pressure_min = m.pressure_pa;
+            self.pressure_max = m.pressure_pa;
+        } else {
+            self.temp_min = self.temp_min.min(m.temperature_c);
+            self.temp_max = self.temp_max.max(m.temperature_c);
+            self.pressure_min = self.pressure_min.min(m.pressure_pa);
+            self.pressure_max = self.pressure_max.max(m.pressure_pa);
+        }
+        self.temp_sum += m.temperature_c;
+        self.pressure_sum += m.pressure_pa;
+        self.count += 1;
+    }
+
+    /// Number of measurements accounted for
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// Lowest temperature in degrees Celsius, `None` before any measurement
+    pub fn temperature_min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.temp_min)
+    }
+
+    /// Highest temperature in degrees Celsius, `None` before any measurement
+    pub fn temperature_max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.temp_max)
+    }
+
+    /// Mean temperature in degrees Celsius, `None` before any measurement
+    pub fn temperature_mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.temp_sum / self.count as f64)
+    }
+
+    /// Lowest pressure in Pascals, `None` before any measurement
+    pub fn pressure_min(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.pressure_min)
+    }
+
+    /// Highest pressure in Pascals, `None` before any measurement
+    pub fn pressure_max(&self) -> Option<f64> {
+        (self.count > 0).then_some(self.pressure_max)
+    }
+
+    /// Mean pressure in Pascals, `None` before any measurement
+    pub fn pressure_mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.pressure_sum / self.count as f64)
+    }
+}
+
+/// Iterator returned by [`BMP280::iter_measurements`]
+pub struct Measurements<'a, I2C: ehal::i2c::I2c, D, MODE = mode::Any> {
+    chip: &'a mut BMP280<I2C, MODE>,
+    delay: &'a mut D,
+    interval_ms: u32,
+    first: bool,
+}
+
+impl<I2C, D, MODE> Iterator for Measurements<'_, I2C, D, MODE>
+where
+    I2C: ehal::i2c::I2c,
+    D: ehal::delay::DelayNs,
+    MODE: mode::AllowsForced,
+{
+    type Item = Result<Measurement, I2C::Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.first {
+            self.delay.delay_ms(self.interval_ms);
+        }
+        self.first = false;
+
+        Some(self.chip.measure_forced(self.delay))
+    }
+}
+
+/// Reading tagged with the time it was taken
 #[derive(Debug, Copy, Clone)]
+pub struct TimedMeasurement<T> {
+    /// The reading
+    pub measurement: Measurement,
+    /// When the reading was taken
+    pub timestamp: T,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 /// Control
 pub struct Control {
     /// Temperature oversampling
@@ -228,7 +1398,8 @@ pub struct Control {
     pub mode: PowerMode,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[allow(non_camel_case_types)]
 /// Standby time in ms
 pub enum Standby {
@@ -252,7 +1423,45 @@ pub enum Standby {
     unknown,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl TryFrom<u8> for Standby {
+    type Error = u8;
+
+    /// Decodes the `t_sb` bits of the config register, returning them back
+    /// if out of range
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        Ok(match bits {
+            0b000 => Standby::ms0_5,
+            0b001 => Standby::ms62_5,
+            0b010 => Standby::ms125,
+            0b011 => Standby::ms250,
+            0b100 => Standby::ms500,
+            0b101 => Standby::ms1000,
+            0b110 => Standby::ms2000,
+            0b111 => Standby::ms4000,
+            _ => return Err(bits),
+        })
+    }
+}
+
+impl Standby {
+    /// Inactive duration in normal mode, `None` if unknown
+    pub fn milliseconds(&self) -> Option<f32> {
+        match self {
+            Standby::ms0_5 => Some(0.5),
+            Standby::ms62_5 => Some(62.5),
+            Standby::ms125 => Some(125.0),
+            Standby::ms250 => Some(250.0),
+            Standby::ms500 => Some(500.0),
+            Standby::ms1000 => Some(1000.0),
+            Standby::ms2000 => Some(2000.0),
+            Standby::ms4000 => Some(4000.0),
+            Standby::unknown => None,
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[allow(non_camel_case_types)]
 /// The time constant of IIR filter
 pub enum Filter {
@@ -270,12 +1479,43 @@ pub enum Filter {
     unknown,
 }
 
+impl TryFrom<u8> for Filter {
+    type Error = u8;
+
+    /// Decodes the `filter` bits of the config register, returning them
+    /// back if they aren't one of the documented values
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        Ok(match bits {
+            0x00 => Filter::off,
+            0x01 => Filter::c2,
+            0x02 => Filter::c4,
+            0x03 => Filter::c8,
+            0x04 => Filter::c16,
+            _ => return Err(bits),
+        })
+    }
+}
+
+impl Filter {
+    /// IIR filter coefficient, `None` when off or unknown
+    pub fn coefficient(&self) -> Option<u8> {
+        match self {
+            Filter::c2 => Some(2),
+            Filter::c4 => Some(4),
+            Filter::c8 => Some(8),
+            Filter::c16 => Some(16),
+            Filter::off | Filter::unknown => None,
+        }
+    }
+}
+
 /// Configuration register, sets the rate, filter and interface options
 /// of the device. Note that writing to this register while device in normal
 /// mode may be ignored. Writes in sleep mode are not ignored.
 ///
 /// spi3w_en is intentionally left out of this implementation.
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 pub struct Config {
     /// Controls inactive duration in normal mode
     pub t_sb: Standby,
@@ -292,6 +1532,20 @@ pub struct Status {
     im_update: bool,
 }
 
+/// The datasheet's weather monitoring settings
+impl Default for Control {
+    fn default() -> Self {
+        Preset::WeatherMonitoring.settings().0
+    }
+}
+
+/// The datasheet's weather monitoring settings
+impl Default for Config {
+    fn default() -> Self {
+        Preset::WeatherMonitoring.settings().1
+    }
+}
+
 impl fmt::Display for Status {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         core::write!(
@@ -303,7 +1557,66 @@ impl fmt::Display for Status {
     }
 }
 
-#[derive(Debug, Copy, Clone)]
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Measurement {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        // ufmt has no float support, print centidegrees as fixed point
+        let centi = (self.temperature_c * 100.0) as i32;
+        let sign = if centi < 0 { "-" } else { "" };
+        let centi = centi.unsigned_abs();
+        ufmt::uwrite!(
+            f,
+            "{}{}.{}{} C, {} Pa",
+            sign,
+            centi / 100,
+            centi / 10 % 10,
+            centi % 10,
+            self.pressure_pa as u32
+        )
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDebug for Measurement {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uwrite!(f, "Measurement {{ {} }}", self)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Config {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uwrite!(f, "standby: {:?}, filter: {:?}", self.t_sb, self.filter)
+    }
+}
+
+#[cfg(feature = "ufmt")]
+impl ufmt::uDisplay for Control {
+    fn fmt<W>(&self, f: &mut ufmt::Formatter<'_, W>) -> Result<(), W::Error>
+    where
+        W: ufmt::uWrite + ?Sized,
+    {
+        ufmt::uwrite!(
+            f,
+            "temperature oversampling: {:?}, pressure oversampling: {:?}, mode: {:?}",
+            self.osrs_t,
+            self.osrs_p,
+            self.mode
+        )
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 #[allow(non_camel_case_types)]
 /// Oversampling
 pub enum Oversampling {
@@ -321,7 +1634,26 @@ pub enum Oversampling {
     x16 = 0b101,
 }
 
-#[derive(Debug, Copy, Clone)]
+impl TryFrom<u8> for Oversampling {
+    type Error = u8;
+
+    /// Decodes the `osrs_t` or `osrs_p` bits of the ctrl_meas register,
+    /// 0b110 and 0b111 also meaning x16, returning them back if out of range
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        Ok(match bits {
+            0b000 => Oversampling::skipped,
+            0b001 => Oversampling::x1,
+            0b010 => Oversampling::x2,
+            0b011 => Oversampling::x4,
+            0b100 => Oversampling::x8,
+            0b101..=0b111 => Oversampling::x16,
+            _ => return Err(bits),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "ufmt", derive(ufmt::derive::uDebug))]
 /// PowerMode
 pub enum PowerMode {
     /// Sleep
@@ -332,6 +1664,96 @@ pub enum PowerMode {
     Normal = 0b11,
 }
 
+impl TryFrom<u8> for PowerMode {
+    type Error = u8;
+
+    /// Decodes the `mode` bits of the ctrl_meas register, 0b10 also meaning
+    /// forced, returning them back if out of range
+    fn try_from(bits: u8) -> Result<Self, Self::Error> {
+        Ok(match bits {
+            0b00 => PowerMode::Sleep,
+            0b01 | 0b10 => PowerMode::Forced,
+            0b11 => PowerMode::Normal,
+            _ => return Err(bits),
+        })
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// Recommended settings per use case (datasheet section 3.4)
+pub enum Preset {
+    /// Handheld device low-power (e.g. Android)
+    HandheldLowPower,
+    /// Handheld device dynamic (e.g. Android)
+    HandheldDynamic,
+    /// Weather monitoring (lowest power)
+    WeatherMonitoring,
+    /// Elevator / floor change detection
+    FloorChange,
+    /// Drop detection
+    DropDetection,
+    /// Indoor navigation
+    IndoorNavigation,
+}
+
+impl Preset {
+    fn settings(self) -> (Control, Config) {
+        let (mode, osrs_p, osrs_t, filter, t_sb) = match self {
+            Preset::HandheldLowPower => (
+                PowerMode::Normal,
+                Oversampling::x16,
+                Oversampling::x2,
+                Filter::c4,
+                Standby::ms62_5,
+            ),
+            Preset::HandheldDynamic => (
+                PowerMode::Normal,
+                Oversampling::x4,
+                Oversampling::x1,
+                Filter::c16,
+                Standby::ms0_5,
+            ),
+            Preset::WeatherMonitoring => (
+                PowerMode::Forced,
+                Oversampling::x1,
+                Oversampling::x1,
+                Filter::off,
+                Standby::ms0_5,
+            ),
+            Preset::FloorChange => (
+                PowerMode::Normal,
+                Oversampling::x4,
+                Oversampling::x1,
+                Filter::c4,
+                Standby::ms125,
+            ),
+            Preset::DropDetection => (
+                PowerMode::Normal,
+                Oversampling::x2,
+                Oversampling::x1,
+                Filter::off,
+                Standby::ms0_5,
+            ),
+            Preset::IndoorNavigation => (
+                PowerMode::Normal,
+                Oversampling::x16,
+                Oversampling::x2,
+                Filter::c16,
+                Standby::ms0_5,
+            ),
+        };
+
+        (
+            Control {
+                osrs_t,
+                osrs_p,
+                mode,
+            },
+            Config { t_sb, filter },
+        )
+    }
+}
+
 #[allow(non_camel_case_types)]
 enum Register {
     id = 0xD0,
@@ -342,3 +1764,102 @@ enum Register {
     press = 0xF7,
     calib00 = 0x88,
 }
+
+/// Power modes a [`BMP280`] can be restricted to at compile time, with
+/// [`into_normal`](BMP280::into_normal) and
+/// [`into_forced`](BMP280::into_forced), so e.g. a forced conversion can't be
+/// triggered on a chip meant to sample on its own:
+///
+/// ```
+/// # use embedded_hal_mock::eh1::delay::NoopDelay;
+/// # use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+/// # let read = |register: u8, data: &[u8]| {
+/// #     [
+/// #         Transaction::transaction_start(0x76),
+/// #         Transaction::write(0x76, vec![register]),
+/// #         Transaction::read(0x76, data.to_vec()),
+/// #         Transaction::transaction_end(0x76),
+/// #     ]
+/// # };
+/// # let write = |register: u8, value: u8| {
+/// #     [Transaction::write_read(0x76, vec![register, value], vec![0])]
+/// # };
+/// # // The chip of the compensation example of the datasheet
+/// # let calibration = [
+/// #     0x70, 0x6b, 0x43, 0x67, 0x18, 0xfc, 0x7d, 0x8e, 0x43, 0xd6, 0xd0, 0x0b,
+/// #     0x27, 0x0b, 0x8c, 0x00, 0xf9, 0xff, 0x8c, 0x3c, 0xf8, 0xc6, 0x70, 0x17,
+/// # ];
+/// # let data = [0x65, 0x5a, 0xc0, 0x7e, 0xed, 0x00];
+/// # let expectations = [
+/// #     &read(0xD0, &[0x58])[..],
+/// #     &read(0x88, &calibration),
+/// #     &read(0xF4, &[0x00]),
+/// #     &write(0xF4, 0x00),
+/// #     &read(0xF4, &[0x00]),
+/// #     &write(0xF4, 0x01),
+/// #     &read(0xF3, &[0x00]),
+/// #     &read(0xF7, &data),
+/// # ]
+/// # .concat();
+/// # let mut mock = Mock::new(&expectations);
+/// # let i2c = mock.clone();
+/// # let mut delay = NoopDelay::new();
+/// # use embedded_nov_2024::bmp280::*;
+/// let mut bmp = BMP280::new(i2c)?.into_forced()?;
+/// let measurement = bmp.measure_forced(&mut delay)?;
+/// // bmp.wait_for_data_ready(&mut delay, 50_000) doesn't compile
+/// # mock.done();
+/// # Ok::<(), embedded_hal_1::i2c::ErrorKind>(())
+/// ```
+pub mod mode {
+    /// No restriction, the mode being whatever was last set, the default
+    pub struct Any;
+    /// Conversions running continuously, see [`PowerMode::Normal`](super::PowerMode::Normal)
+    pub struct Normal;
+    /// Conversions on demand, the chip sleeping in between
+    pub struct Forced;
+
+    use super::PowerMode;
+
+    /// Every mode, each keeping the chip to the power modes it allows
+    pub trait Mode: sealed::Sealed {
+        /// The power mode actually set when `requested` is
+        fn restrict(requested: PowerMode) -> PowerMode;
+    }
+
+    /// Modes with the normal mode API, e.g. waiting for the next conversion
+    pub trait AllowsNormal: sealed::Sealed {}
+    /// Modes with the forced mode API, e.g. triggering a conversion
+    pub trait AllowsForced: sealed::Sealed {}
+
+    impl Mode for Any {
+        fn restrict(requested: PowerMode) -> PowerMode {
+            requested
+        }
+    }
+    impl Mode for Normal {
+        fn restrict(_requested: PowerMode) -> PowerMode {
+            PowerMode::Normal
+        }
+    }
+    impl Mode for Forced {
+        fn restrict(requested: PowerMode) -> PowerMode {
+            match requested {
+                PowerMode::Normal => PowerMode::Sleep,
+                mode => mode,
+            }
+        }
+    }
+
+    impl AllowsNormal for Any {}
+    impl AllowsNormal for Normal {}
+    impl AllowsForced for Any {}
+    impl AllowsForced for Forced {}
+
+    mod sealed {
+        pub trait Sealed {}
+        impl Sealed for super::Any {}
+        impl Sealed for super::Normal {}
+        impl Sealed for super::Forced {}
+    }
+}
@@ -0,0 +1,57 @@
+//! Piezo buzzer driven by a PWM slice, square waves at 50% duty. The pitch is
+//! set through the slice's divider and top, so the buzzer needs a slice of
+//! its own: any other output on it would change frequency along with it.
+
+use embassy_rp::clocks::clk_sys_freq;
+use embassy_rp::pwm::{Config, Pwm};
+use embassy_time::{Duration, Timer};
+
+/// Piezo buzzer on either output of a PWM slice
+pub struct Buzzer<'d> {
+    pwm: Pwm<'d>,
+    config: Config,
+}
+
+impl<'d> Buzzer<'d> {
+    /// Takes over `pwm`, silent until asked to play
+    pub fn new(pwm: Pwm<'d>) -> Self {
+        let mut config = Config::default();
+        config.compare_a = 0;
+        config.compare_b = 0;
+        config.enable = false;
+        let mut buzzer = Buzzer { pwm, config };
+        buzzer.pwm.set_config(&buzzer.config);
+        buzzer
+    }
+
+    /// Plays a tone of `freq_hz` until told otherwise, nothing below 1 Hz
+    pub fn tone(&mut self, freq_hz: u32) {
+        let cycles = clk_sys_freq() / freq_hz.max(1);
+        // The top is 16 bits, the integer part of the divider 8 bits
+        let divider = cycles.div_ceil(1 << 16).clamp(1, 255);
+        let top = (cycles / divider).clamp(2, 1 << 16) - 1;
+
+        self.config.divider = (divider as u8).into();
+        self.config.top = top as u16;
+        self.config.compare_a = self.config.top / 2;
+        self.config.compare_b = self.config.top / 2;
+        self.config.enable = true;
+        self.pwm.set_config(&self.config);
+    }
+
+    /// Stops playing, leaving the output low
+    pub fn silence(&mut self) {
+        // The compare values only latch when the counter wraps, so the slice
+        // keeps running: disabled, it would freeze the output, maybe high
+        self.config.compare_a = 0;
+        self.config.compare_b = 0;
+        self.pwm.set_config(&self.config);
+    }
+
+    /// Plays a tone of `freq_hz` for `duration`, silent afterwards
+    pub async fn beep(&mut self, freq_hz: u32, duration: Duration) {
+        self.tone(freq_hz);
+        Timer::after(duration).await;
+        self.silence();
+    }
+}
@@ -23,57 +23,125 @@ use embassy_rp::gpio::{Output, Pin};
 use embassy_rp::peripherals::{PIN_0, PIN_1, PIN_2, PWM_SLICE0, PWM_SLICE1};
 use embassy_rp::pwm::{Config as PwmConfig, Pwm, SetDutyCycle};
 use embassy_time::Timer;
+use embedded_nov_2024::rgb_led::{lerp_rgb, RgbLed};
 use {defmt_rtt as _, panic_probe as _};
 
-fn get_min(a: u16, b: u16) -> u16 {
-    if a > b {
-        b
-    } else {
-        a
+/// Duration of one fade frame.
+const FRAME_MS: u64 = 50;
+/// Number of frames in the 1 second transition.
+const TRANSITION_FRAMES: u16 = 20;
+/// How long each color is displayed before fading to the next one.
+const HOLD_SECS: u64 = 3;
+
+/// Curve applied to the fade.
+const EASING: Easing = Easing::EaseInOut;
+
+/// Correction for this particular LED, tune until full white looks neutral.
+const CALIBRATION: ChannelCalibration = ChannelCalibration {
+    r_scale: 1.0,
+    g_scale: 1.0,
+    b_scale: 1.0,
+};
+
+/// Per-channel duty scaling, compensating for the LED's color cast.
+#[derive(Clone, Copy)]
+struct ChannelCalibration {
+    r_scale: f32,
+    g_scale: f32,
+    b_scale: f32,
+}
+
+impl ChannelCalibration {
+    /// Scales each channel of `color`, keeping it within the PWM range.
+    fn apply(self, color: (u16, u16, u16)) -> (u16, u16, u16) {
+        (
+            scale(color.0, self.r_scale),
+            scale(color.1, self.g_scale),
+            scale(color.2, self.b_scale),
+        )
+    }
+}
+
+/// Scales a single channel, clamped to the PWM top.
+fn scale(value: u16, factor: f32) -> u16 {
+    (value as f32 * factor).clamp(0.0, 255.0) as u16
+}
+
+/// Shape of the transition between two colors.
+#[derive(Clone, Copy)]
+enum Easing {
+    Linear,
+    EaseIn,
+    EaseOut,
+    EaseInOut,
+}
+
+impl Easing {
+    /// Maps the linear progress `t` (0 to 1) onto the curve.
+    fn apply(self, t: f32) -> f32 {
+        match self {
+            Easing::Linear => t,
+            Easing::EaseIn => t * t,
+            Easing::EaseOut => t * (2.0 - t),
+            Easing::EaseInOut => {
+                if t < 0.5 {
+                    2.0 * t * t
+                } else {
+                    -1.0 + (4.0 - 2.0 * t) * t
+                }
+            }
+        }
     }
 }
-fn blue_config(color: (u16, u16, u16)) -> PwmConfig {
-    let mut config = PwmConfig::default();
-    config.top = 255;
-    config.compare_a = color.1;
-    config.compare_a = color.2;
 
-    config
+/// Parses a color written as "#RRGGBB" (the `#` being optional).
+fn parse_hex(s: &str) -> Option<(u16, u16, u16)> {
+    let hex = s.strip_prefix('#').unwrap_or(s);
+    if hex.len() != 6 || !hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(u16::from);
+    Some((channel(0)?, channel(2)?, channel(4)?))
 }
 
 #[embassy_executor::main]
 async fn main(spawner: Spawner) {
     let p = embassy_rp::init(Default::default());
 
-    let color1 = (109, 63, 91);
-    let color2 = (255, 164, 32);
-    let mut color = color1;
-
-    let mut config = PwmConfig::default();
-    config.top = 255;
-    config.compare_a = 109;
-    config.compare_b = 63;
+    let color1 = parse_hex("#6D3F5B").unwrap();
+    let color2 = parse_hex("#FFA420").unwrap();
+    let (mut from, mut to) = (color1, color2);
 
-    let mut pwm_rg = Pwm::new_output_ab(p.PWM_SLICE0, p.PIN_0, p.PIN_1, config.clone());
+    let mut pwm_rg = Pwm::new_output_ab(p.PWM_SLICE0, p.PIN_0, p.PIN_1, PwmConfig::default());
+    let mut pwm_b = Pwm::new_output_a(p.PWM_SLICE1, p.PIN_2, PwmConfig::default());
+    let (red, green) = pwm_rg.split_by_ref();
+    let (blue, _) = pwm_b.split_by_ref();
+    let mut led = RgbLed::new(red.unwrap(), green.unwrap(), blue.unwrap());
 
-    let mut pwm_b = Pwm::new_output_a(p.PWM_SLICE1, p.PIN_2, blue_config(color));
+    let start = CALIBRATION.apply(from);
+    led.set_rgb(start.0, start.1, start.2);
 
     loop {
-        Timer::after_millis(50).await;
-        pwm_rg.set_config(&config);
-        pwm_b.set_config(&blue_config(color1));
+        Timer::after_secs(HOLD_SECS).await;
 
-        info!("r={}, g={}, b={}", color1.0, color1.1, color1.2);
+        for frame in 1..=TRANSITION_FRAMES {
+            let t = EASING.apply(frame as f32 / TRANSITION_FRAMES as f32);
+            let color = lerp_rgb(from, to, t);
+            let duty = CALIBRATION.apply(color);
+            led.set_rgb(duty.0, duty.1, duty.2);
 
-        if color == color2 {
-            color = color1;
-        }
+            info!(
+                "r={}, g={}, b={}, progress={}%",
+                color.0,
+                color.1,
+                color.2,
+                frame * 100 / TRANSITION_FRAMES
+            );
 
-        color.0 = get_min(color.0 + 1, color2.0);
-        color.1 = get_min(color.1 + 1, color2.1);
-        color.2 = get_min(color.2 + 1, color2.2);
+            Timer::after_millis(FRAME_MS).await;
+        }
 
-        config.compare_a = color.0;
-        config.compare_b = color.1;
+        core::mem::swap(&mut from, &mut to);
     }
 }
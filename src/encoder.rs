@@ -0,0 +1,85 @@
+//! Quadrature rotary encoder on two GPIOs, read through a state table that
+//! only accepts transitions to a neighbouring state. Contact bounce flips a
+//! single channel back and forth, whose steps cancel out, so the encoder
+//! needs no debounce delay of its own:
+//!
+//! ```ignore
+//! let mut encoder = Encoder::new(
+//!     Input::new(p.PIN_26, Pull::Up),
+//!     Input::new(p.PIN_27, Pull::Up),
+//! );
+//! loop {
+//!     match encoder.step().await {
+//!         Direction::Clockwise => value += 1,
+//!         Direction::CounterClockwise => value -= 1,
+//!     }
+//! }
+//! ```
+
+use embassy_futures::select::select;
+use embassy_rp::gpio::Input;
+
+/// Transitions between two detents of the usual (e.g. EC11) encoders
+const TRANSITIONS_PER_DETENT: i8 = 4;
+
+/// Step caused by going from the state in the upper two bits of the index to
+/// the one in the lower two, 0 for no change or an invalid jump of both
+/// channels at once
+const TRANSITIONS: [i8; 16] = [0, -1, 1, 0, 1, 0, 0, -1, -1, 0, 0, 1, 0, 1, -1, 0];
+
+/// Which way the encoder was turned
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Direction {
+    /// A leading B
+    Clockwise,
+    /// B leading A
+    CounterClockwise,
+}
+
+/// Rotary encoder with its A and B channels on two inputs, swap them to
+/// reverse the direction
+pub struct Encoder<'d> {
+    a: Input<'d>,
+    b: Input<'d>,
+    state: u8,
+    transitions: i8,
+}
+
+impl<'d> Encoder<'d> {
+    /// Reads the encoder on inputs `a` and `b`, pulled up by the caller
+    pub fn new(a: Input<'d>, b: Input<'d>) -> Self {
+        let mut encoder = Encoder {
+            a,
+            b,
+            state: 0,
+            transitions: 0,
+        };
+        encoder.state = encoder.read();
+        encoder
+    }
+
+    /// Waits for the encoder to be turned by one detent
+    pub async fn step(&mut self) -> Direction {
+        loop {
+            select(self.a.wait_for_any_edge(), self.b.wait_for_any_edge()).await;
+
+            let state = self.read();
+            self.transitions += TRANSITIONS[(self.state << 2 | state) as usize];
+            self.state = state;
+
+            if self.transitions >= TRANSITIONS_PER_DETENT {
+                self.transitions = 0;
+                return Direction::Clockwise;
+            }
+            if self.transitions <= -TRANSITIONS_PER_DETENT {
+                self.transitions = 0;
+                return Direction::CounterClockwise;
+            }
+        }
+    }
+
+    /// Both channels as the two lowest bits, A above B
+    fn read(&self) -> u8 {
+        (self.a.is_high() as u8) << 1 | self.b.is_high() as u8
+    }
+}
@@ -1,4 +1,11 @@
 #![no_std]
 
+pub mod blink;
 pub mod bmp280;
+pub mod buzzer;
 pub mod display;
+pub mod encoder;
+pub mod prelude;
+pub mod retry_i2c;
+pub mod rgb_led;
+pub mod sensors;
@@ -0,0 +1,8 @@
+//! Commonly used types, meant to be imported with
+//! `use embedded_nov_2024::prelude::*;`
+
+pub use crate::bmp280::{
+    Alert, BoundsWatcher, CalibrationData, Config, Control, Filter, Measurement, Oversampling,
+    PowerMode, Preset, SessionStats, SplitReads, Standby, Status, TimedMeasurement, BMP280,
+};
+pub use crate::sensors::Sensor;
@@ -0,0 +1,113 @@
+//! I2C bus adapter retrying failed transactions, so any driver on top of it
+//! (the BMP280, the EEPROM, ...) rides out transient bus errors without
+//! retry logic of its own:
+//!
+//! ```ignore
+//! let policy = Backoff::new(Delay, 3, 100);
+//! let mut bmp = BMP280::new(RetryI2c::new(i2c, policy))?;
+//! ```
+
+use embedded_hal_1 as ehal;
+
+use ehal::delay::DelayNs;
+use ehal::i2c::{Error, ErrorKind, ErrorType, I2c, Operation};
+
+/// Decides whether a failed transaction is attempted again
+pub trait RetryPolicy {
+    /// Called after the failed attempt number `attempt`, starting at 1, with
+    /// the kind of error it failed with. Returns whether to try again, after
+    /// waiting as long as the policy wants to
+    fn retry(&mut self, attempt: u32, kind: ErrorKind) -> bool;
+}
+
+/// Any `FnMut(attempt, kind) -> bool` closure is a policy
+impl<F: FnMut(u32, ErrorKind) -> bool> RetryPolicy for F {
+    fn retry(&mut self, attempt: u32, kind: ErrorKind) -> bool {
+        self(attempt, kind)
+    }
+}
+
+/// Retries errors that may go away on their own, waiting twice as long
+/// after each failed attempt
+pub struct Backoff<D> {
+    delay: D,
+    max_attempts: u32,
+    initial_wait_us: u32,
+}
+
+impl<D: DelayNs> Backoff<D> {
+    /// Makes up to `max_attempts` attempts in total, waiting `initial_wait_us`
+    /// after the first failure
+    pub fn new(delay: D, max_attempts: u32, initial_wait_us: u32) -> Self {
+        Backoff {
+            delay,
+            max_attempts,
+            initial_wait_us,
+        }
+    }
+}
+
+impl<D: DelayNs> RetryPolicy for Backoff<D> {
+    fn retry(&mut self, attempt: u32, kind: ErrorKind) -> bool {
+        if attempt >= self.max_attempts || !is_transient(kind) {
+            return false;
+        }
+
+        let wait_us = self
+            .initial_wait_us
+            .saturating_mul(1 << (attempt - 1).min(31));
+        self.delay.delay_us(wait_us);
+        true
+    }
+}
+
+/// Whether an error of this kind may not happen again on the next attempt,
+/// e.g. a device busy with a write not acknowledging its address
+pub fn is_transient(kind: ErrorKind) -> bool {
+    matches!(
+        kind,
+        ErrorKind::Bus
+            | ErrorKind::ArbitrationLoss
+            | ErrorKind::NoAcknowledge(_)
+            | ErrorKind::Overrun
+    )
+}
+
+/// Wraps an I2C bus, retrying every failed transaction as `policy` decides
+pub struct RetryI2c<I2C, P> {
+    i2c: I2C,
+    policy: P,
+}
+
+impl<I2C: I2c, P: RetryPolicy> RetryI2c<I2C, P> {
+    /// Retries the transactions on `i2c` according to `policy`
+    pub fn new(i2c: I2C, policy: P) -> Self {
+        RetryI2c { i2c, policy }
+    }
+
+    /// Gives back the wrapped bus and the policy
+    pub fn release(self) -> (I2C, P) {
+        (self.i2c, self.policy)
+    }
+}
+
+impl<I2C: I2c, P> ErrorType for RetryI2c<I2C, P> {
+    type Error = I2C::Error;
+}
+
+impl<I2C: I2c, P: RetryPolicy> I2c for RetryI2c<I2C, P> {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        let mut attempt = 1;
+        loop {
+            match self.i2c.transaction(address, operations) {
+                Ok(()) => return Ok(()),
+                Err(e) if self.policy.retry(attempt, e.kind()) => attempt += 1,
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
@@ -0,0 +1,92 @@
+//! Common anode RGB LED driven by PWM, shared by the color and smart clock
+//! examples.
+//!
+//! Each channel is anything implementing [`SetDutyCycle`], e.g. the outputs
+//! of [`embassy_rp::pwm::Pwm::split_by_ref`], or a mock recording the duty
+//! cycles when testing off target.
+
+use embassy_time::{Duration, Timer};
+use embedded_hal_1::pwm::SetDutyCycle;
+
+/// Duration of one step of [`RgbLed::fade_to`]
+const FADE_FRAME: Duration = Duration::from_millis(20);
+
+/// RGB LED, with 8 bit channels (0 to 255)
+pub struct RgbLed<R, G, B> {
+    red: R,
+    green: G,
+    blue: B,
+    color: (u16, u16, u16),
+    brightness: u8,
+}
+
+impl<R: SetDutyCycle, G: SetDutyCycle, B: SetDutyCycle> RgbLed<R, G, B> {
+    /// Takes over the three channels, starting off at full brightness
+    pub fn new(red: R, green: G, blue: B) -> Self {
+        let mut led = RgbLed {
+            red,
+            green,
+            blue,
+            color: (0, 0, 0),
+            brightness: 100,
+        };
+        led.set_rgb(0, 0, 0);
+        led
+    }
+
+    /// Last color set
+    pub fn color(&self) -> (u16, u16, u16) {
+        self.color
+    }
+
+    /// Brightness in percent
+    pub fn brightness(&self) -> u8 {
+        self.brightness
+    }
+
+    /// Scales every channel to `percent` (capped at 100) of its value, also
+    /// applied to the current color
+    pub fn set_brightness(&mut self, percent: u8) {
+        self.brightness = percent.min(100);
+        let (r, g, b) = self.color;
+        self.set_rgb(r, g, b);
+    }
+
+    /// Switches to the color right away
+    pub fn set_rgb(&mut self, r: u16, g: u16, b: u16) {
+        self.color = (r, g, b);
+
+        let dim = |value: u16| (value.min(255) as u32 * self.brightness as u32 / 100) as u16;
+        set_channel(&mut self.red, dim(r));
+        set_channel(&mut self.green, dim(g));
+        set_channel(&mut self.blue, dim(b));
+    }
+
+    /// Fades linearly from the current color to `target` over `duration`
+    pub async fn fade_to(&mut self, target: (u16, u16, u16), duration: Duration) {
+        let from = self.color;
+        let frames = (duration.as_ticks() / FADE_FRAME.as_ticks()).max(1);
+        for frame in 1..=frames {
+            let (r, g, b) = lerp_rgb(from, target, frame as f32 / frames as f32);
+            self.set_rgb(r, g, b);
+            Timer::after(FADE_FRAME).await;
+        }
+    }
+}
+
+/// Common anode, the LED lights while the pin is low, so the duty cycle is
+/// the complement of the value
+fn set_channel(channel: &mut impl SetDutyCycle, value: u16) {
+    // Can't fail, the fraction is at most 1
+    let _ = channel.set_duty_cycle_fraction(255 - value.min(255), 255);
+}
+
+/// Interpolates between colors `a` and `b`, `t` going from 0 to 1
+pub fn lerp_rgb(a: (u16, u16, u16), b: (u16, u16, u16), t: f32) -> (u16, u16, u16) {
+    (mix(a.0, b.0, t), mix(a.1, b.1, t), mix(a.2, b.2, t))
+}
+
+/// Interpolates a single channel between `a` and `b`, `t` going from 0 to 1
+fn mix(a: u16, b: u16, t: f32) -> u16 {
+    (a as f32 + (b as f32 - a as f32) * t) as u16
+}
@@ -0,0 +1,26 @@
+//! Uniform interface over the sensors in this crate, so generic code (e.g. a
+//! logging task) can poll any of them.
+
+use embedded_hal_1 as ehal;
+
+use crate::bmp280::{Measurement, BMP280};
+
+/// A sensor producing readings
+pub trait Sensor {
+    /// What a single reading contains
+    type Reading;
+    /// Error returned when reading fails
+    type Error;
+
+    /// Takes a reading
+    fn read(&mut self) -> Result<Self::Reading, Self::Error>;
+}
+
+impl<I2C: ehal::i2c::I2c, MODE> Sensor for BMP280<I2C, MODE> {
+    type Reading = Measurement;
+    type Error = I2C::Error;
+
+    fn read(&mut self) -> Result<Measurement, I2C::Error> {
+        self.measure()
+    }
+}
@@ -5,15 +5,23 @@
 //! to get the current time at the beginning of the runtime.
 //!     * It will have provide a visual feedback of the current temperature
 //! by displaying a color between red and blue on the RGB led, depending on
-//! configurable maximum and minimum thresholds.
-//!     * In order to update the thresholds, the desired behaviour is to
-//! enter the configure mode by pressing the A button, then the current minimum
-//! threshold value will be displayed on the screen, and by pressing X and Y,
-//! the user should be able to increase and decrease respectively be half a
-//! degree, then confirm it by pressing A once again, and proceed to setting
-//! the maximum threshold in the same fashion.
+//! configurable maximum and minimum thresholds, and beeping a piezo buzzer
+//! on GP8 whenever the temperature crosses out of them.
+//!     * The settings are changed from a menu, opened by pressing the A
+//! button. X and Y move between its entries: the minimum and maximum
+//! thresholds, the temperature unit, the time format, the display and LED
+//! brightness, and saving and exiting. A selects the entry under the cursor,
+//! toggling the unit and the time format right away. A threshold or the
+//! brightness is then increased with X and decreased with Y, by half a degree
+//! or 10%, and A confirms it and returns to the menu.
+//! Outside the menu, X and Y switch between the temperature, a page with the
+//! pressure and the altitude it works out to, and one with the WiFi signal
+//! strength, to help find a spot with reliable connectivity.
+//! A rotary encoder on GP26 and GP27 can stand in for X and Y, clockwise
+//! turns acting as X presses.
 //!     * To ensure redundency, the thresholds will be written in the provided
-//! EEPROM24C256 when set, and read at the beginning of the program.
+//! EEPROM24C256 when set, and read at the beginning of the program. Holding
+//! X and Y while the clock boots wipes them, restoring the defaults.
 //!     * BONUS: We will simulate the fact that the clock is part of an evil
 //! IoT network that spies on its users by sending a JSON package via HTTPS
 //! to the same server, containing the datetime and the temperature.
@@ -21,271 +29,2348 @@
 #![no_std]
 #![no_main]
 
+// A bin crate root looks up modules next to itself, among the library ones
+#[path = "smart_clock/format.rs"]
+mod format;
+#[cfg(not(feature = "offline"))]
+#[path = "smart_clock/time_api.rs"]
+mod time_api;
+
+use core::cell::RefCell;
+use core::fmt::Write;
+use core::panic::PanicInfo;
+#[cfg(not(feature = "offline"))]
 use core::str::from_utf8;
-use cyw43::JoinOptions;
+use core::sync::atomic::{AtomicBool, Ordering};
+#[cfg(not(feature = "offline"))]
+use cyw43::{JoinOptions, ScanOptions};
+#[cfg(not(feature = "offline"))]
 use cyw43_pio::PioSpi;
-use defmt::*;
+use defmt::{error, info, warn, Display2Format};
+use defmt_rtt as _;
+use eeprom24x::{Eeprom24x, SlaveAddr};
+use embassy_embedded_hal::shared_bus::blocking::i2c::I2cDevice;
+use embassy_embedded_hal::shared_bus::blocking::spi::SpiDeviceWithConfig;
 use embassy_executor::Spawner;
+use embassy_futures::select::{select, select3, Either, Either3};
+#[cfg(not(feature = "offline"))]
 use embassy_net::dns::DnsSocket;
+#[cfg(not(feature = "offline"))]
 use embassy_net::tcp::client::{TcpClient, TcpClientState};
-use embassy_net::{Ipv4Address, Ipv4Cidr, StackResources};
+#[cfg(not(feature = "offline"))]
+use embassy_net::{Ipv4Address, Ipv4Cidr, Stack, StackResources};
+#[cfg(not(feature = "offline"))]
+use embassy_rp::adc::{Adc, Channel as AdcChannel, Config as AdcConfig};
 use embassy_rp::bind_interrupts;
-use embassy_rp::gpio::{Level, Output};
-use embassy_rp::peripherals::{DMA_CH0, PIO0};
-use embassy_rp::pio::{InterruptHandler, Pio};
-use embassy_time::{Duration, Timer};
+use embassy_rp::gpio::{Input, Level, Output, Pull};
+use embassy_rp::i2c::{Config as I2cConfig, I2c};
+#[cfg(not(feature = "offline"))]
+use embassy_rp::peripherals::DMA_CH0;
+use embassy_rp::peripherals::{I2C0, PIO0, RTC, SPI0};
+use embassy_rp::pio::InterruptHandler;
+#[cfg(not(feature = "offline"))]
+use embassy_rp::pio::Pio;
+use embassy_rp::pwm::{Config as PwmConfig, Pwm};
+use embassy_rp::rtc::{DateTime, DayOfWeek, Rtc};
+use embassy_rp::spi::{Blocking, Spi};
+use embassy_sync::blocking_mutex::raw::{CriticalSectionRawMutex, NoopRawMutex};
+use embassy_sync::channel::Channel;
+use embassy_sync::mutex::Mutex;
+use embassy_sync::signal::Signal;
+use embassy_sync::watch::Watch;
+use embassy_time::{Duration, Instant, Timer};
 use embedded_graphics::draw_target::DrawTarget;
-use embedded_graphics::geometry::Point;
-use embedded_graphics::mono_font::iso_8859_1::FONT_7X13_BOLD;
-use embedded_graphics::mono_font::MonoTextStyle;
-use embedded_graphics::pixelcolor::{Rgb565, RgbColor};
-use embedded_graphics::text::Text;
+use embedded_graphics::geometry::{Point, Size};
+use embedded_graphics::mono_font::iso_8859_1::{FONT_10X20, FONT_7X13_BOLD};
+use embedded_graphics::mono_font::{MonoTextStyle, MonoTextStyleBuilder};
+use embedded_graphics::pixelcolor::{BinaryColor, PixelColor, Rgb565, RgbColor};
+use embedded_graphics::primitives::{Primitive, PrimitiveStyle, PrimitiveStyleBuilder, Rectangle};
+use embedded_graphics::text::{Alignment, Text};
 use embedded_graphics::Drawable;
+use embedded_hal_bus::spi::ExclusiveDevice;
+#[cfg(not(feature = "offline"))]
+use embedded_io_async::Read;
+#[cfg(not(feature = "offline"))]
+use embedded_nov_2024::blink::{blink_led_forever, Led};
+#[cfg(not(feature = "offline"))]
+use embedded_nov_2024::bmp280::{altitude_from_pressure, Measurement, Preset, BMP280};
+use embedded_nov_2024::buzzer::Buzzer;
 use embedded_nov_2024::display::SPIDeviceInterface;
+use embedded_nov_2024::encoder::{Direction, Encoder};
+use embedded_nov_2024::rgb_led::RgbLed;
+#[cfg(not(feature = "offline"))]
+use embedded_nov_2024::sensors::Sensor;
+use format::{
+    format_temperature, format_threshold, format_time, TemperatureUnit, Threshold, TimeFormat,
+};
+use heapless::String;
+#[cfg(not(feature = "offline"))]
 use heapless::Vec;
-use reqwless::client::{HttpClient, TlsConfig, TlsVerify};
+#[cfg(not(feature = "offline"))]
+use reqwless::client::HttpClient;
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+use reqwless::client::{TlsConfig, TlsVerify};
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+use reqwless::headers::ContentType;
+#[cfg(not(feature = "offline"))]
 use reqwless::request::Method;
-use serde::Deserialize;
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+use reqwless::request::RequestBuilder;
+#[cfg(feature = "ssd1306")]
+use ssd1306::{mode::BufferedGraphicsMode, prelude::*, I2CDisplayInterface, Ssd1306};
 use static_cell::StaticCell;
-use {defmt_rtt as _, panic_probe as _, serde_json_core};
+#[cfg(not(feature = "offline"))]
+use time_api::{from_timestamp, parse_time, timestamp};
 
 const DISPLAY_FREQ: u32 = 64_000_000;
+const DISPLAY_WIDTH: u16 = 240;
+const DISPLAY_HEIGHT: u16 = 240;
+/// Change this to match how the display is mounted in the enclosure.
+const DISPLAY_ORIENTATION: st7789::Orientation = st7789::Orientation::Portrait;
+
+/// Known WiFi networks as `(ssid, password)`, tried in order until one joins.
+#[cfg(not(feature = "offline"))]
+const WIFI_NETWORKS: &[(&str, &str)] = &[("Wyeiodrin", "g3E2PjWy")];
+
+/// Change to `NetworkConfig::Dhcp` when the network hands out addresses.
+#[cfg(not(feature = "offline"))]
+const NETWORK_CONFIG: NetworkConfig = NetworkConfig::Static {
+    address: Ipv4Cidr::new(Ipv4Address::new(192, 168, 1, 9), 24),
+    gateway: Ipv4Address::new(192, 168, 1, 1),
+};
+
+/// EEPROM location of the index of the last joined WiFi network.
+#[cfg(not(feature = "offline"))]
+const EEPROM_LAST_NETWORK: u32 = 0x0000;
+
+/// EEPROM location of the [`Settings`], the minimum and maximum thresholds,
+/// two bytes each, followed by the brightness and the display choices.
+const EEPROM_SETTINGS: u32 = 0x0010;
+
+/// Unit and time format used until others are saved to the EEPROM.
+const DEFAULT_TEMPERATURE_UNIT: TemperatureUnit = TemperatureUnit::Celsius;
+const DEFAULT_TIME_FORMAT: TimeFormat = TimeFormat::H24;
+
+/// Thresholds used until some are saved to the EEPROM.
+const DEFAULT_THRESHOLDS: Thresholds = Thresholds {
+    min: Threshold::from_celsius(18),
+    max: Threshold::from_celsius(26),
+};
+
+/// Brightness used until one is saved to the EEPROM, in percent.
+const DEFAULT_BRIGHTNESS: u8 = 100;
+/// Brightness change per X or Y press, in percent.
+const BRIGHTNESS_STEP: u8 = 10;
+
+/// How far past a threshold the temperature has to go before the LED switches
+/// to or from the out of range colors, so it doesn't flicker around it.
+const HYSTERESIS: f64 = 0.25;
+
+/// Sea level pressure the altitude is computed against, in Pa. The standard
+/// atmosphere's, set it to the local QNH for the actual elevation.
+#[cfg(not(feature = "offline"))]
+const SEA_LEVEL_PRESSURE: f64 = 101_325.0;
+
+/// How often [`sensor_task`] samples the temperature.
+const SENSOR_INTERVAL: Duration = Duration::from_secs(1);
+/// Readings averaged into the published temperature, so its last digit
+/// doesn't flicker. 1 publishes every reading as is.
+const TEMPERATURE_SMOOTHING: usize = 5;
+
+/// Pitch of the alarm for a temperature above the maximum threshold.
+const ALARM_HOT_HZ: u32 = 2000;
+/// Pitch of the alarm for a temperature below the minimum threshold.
+const ALARM_COLD_HZ: u32 = 1000;
+/// Length of each alarm beep, and of the pause after it.
+const ALARM_BEEP: Duration = Duration::from_millis(150);
+/// Beeps sounded when the temperature crosses out of the thresholds.
+const ALARM_BEEPS: u32 = 3;
+
+/// Presses closer together than this are treated as a single one.
+const BUTTON_DEBOUNCE: Duration = Duration::from_millis(200);
+
+/// Holding X and Y this long while the clock boots wipes the saved settings.
+const FACTORY_RESET_HOLD: Duration = Duration::from_secs(3);
+/// How long the factory reset outcome stays on screen.
+const FACTORY_RESET_NOTICE: Duration = Duration::from_secs(3);
+
+/// The display blanks after this long without a button press.
+const DISPLAY_TIMEOUT: Duration = Duration::from_secs(120);
+/// How often the clock screen is redrawn when no button is pressed.
+const DISPLAY_REFRESH: Duration = Duration::from_secs(1);
+/// How long the splash screen stays up once booting is done.
+const WELCOME_HOLD: Duration = Duration::from_secs(15);
+
+#[cfg(not(feature = "offline"))]
+const TIME_URL: &str = "http://192.168.1.199:5000/time";
+/// Local time offset from the UTC time returned by the server, e.g. 120 for UTC+2.
+#[cfg(not(feature = "offline"))]
+const TZ_OFFSET_MINUTES: i32 = 120;
+/// How often the RTC is re-synced with the time server.
+#[cfg(not(feature = "offline"))]
+const TIME_SYNC_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// Wait after the first failed attempt to get the time at boot, doubling
+/// after each further one up to [`TIME_BOOTSTRAP_MAX_BACKOFF`].
+#[cfg(not(feature = "offline"))]
+const TIME_BOOTSTRAP_BACKOFF: Duration = Duration::from_secs(2);
+#[cfg(not(feature = "offline"))]
+const TIME_BOOTSTRAP_MAX_BACKOFF: Duration = Duration::from_secs(60);
+/// Attempts at boot before starting without the time, [`time_sync_task`]
+/// then keeps trying in the background.
+#[cfg(not(feature = "offline"))]
+const TIME_BOOTSTRAP_ATTEMPTS: u32 = 5;
 
-const WIFI_NETWORK: &str = "Wyeiodrin";
-const WIFI_PASSWORD: &str = "g3E2PjWy";
+/// Time the RTC starts from in the offline demo.
+#[cfg(feature = "offline")]
+const OFFLINE_TIME: DateTime = DateTime {
+    year: 2024,
+    month: 11,
+    day: 23,
+    hour: 12,
+    minute: 0,
+    second: 0,
+    day_of_week: DayOfWeek::Saturday,
+};
 
+/// Onboard LED heartbeat, it stops blinking if the firmware hangs.
+#[cfg(not(feature = "offline"))]
+const HEARTBEAT_ON: Duration = Duration::from_millis(100);
+#[cfg(not(feature = "offline"))]
+const HEARTBEAT_OFF: Duration = Duration::from_millis(900);
+
+/// How often the WiFi signal strength is measured while it is shown, the
+/// heartbeat pausing for the scan.
+#[cfg(not(feature = "offline"))]
+const SIGNAL_INTERVAL: Duration = Duration::from_secs(5);
+/// Signal strength in dBm lighting up each bar of the signal page.
+const SIGNAL_LEVELS: [i16; 4] = [-85, -75, -67, -55];
+
+/// How often [`wifi_task`] checks that the network is still usable.
+#[cfg(not(feature = "offline"))]
+const NETWORK_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+/// How long the link or the address may be missing before rejoining the
+/// WiFi, DHCP usually renews or rediscovers the lease by itself within it.
+#[cfg(not(feature = "offline"))]
+const NETWORK_LOSS_TIMEOUT: Duration = Duration::from_secs(30);
+/// How often the link state is checked while waiting for it to come up.
+#[cfg(not(feature = "offline"))]
+const LINK_POLL: Duration = Duration::from_millis(500);
+
+/// Sockets of the network stack, for DHCP, DNS and the TCP connections.
+#[cfg(not(feature = "offline"))]
+const STACK_SOCKETS: usize = 5;
+/// TCP receive and transmit buffer sizes of the HTTP clients.
+#[cfg(not(feature = "offline"))]
+const TCP_BUFFER_SIZE: usize = 1024;
+/// Buffer the time server response headers are read into.
+#[cfg(not(feature = "offline"))]
+const HTTP_RX_BUFFER_SIZE: usize = 8192;
+/// Longest time server response body accepted, a time takes far less.
+#[cfg(not(feature = "offline"))]
+const TIME_BODY_MAX: usize = 1024;
+/// Size of the pieces the time server response body is read in.
+#[cfg(not(feature = "offline"))]
+const TIME_BODY_CHUNK: usize = 128;
+/// TLS record buffer sizes of the telemetry client, one of each way. The
+/// largest record being 16 KiB plus overhead, less works only with servers
+/// negotiating a smaller maximum fragment length.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+const TLS_BUFFER_SIZE: usize = 16640;
+
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+const TELEMETRY_URL: &str = "https://192.168.1.199:5000/telemetry";
+/// Minimum time between two telemetry reports.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+const TELEMETRY_INTERVAL: Duration = Duration::from_secs(60);
+/// Attempts per report, the wait doubling after each failed one.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+const TELEMETRY_ATTEMPTS: u32 = 4;
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+const TELEMETRY_BACKOFF: Duration = Duration::from_secs(2);
+
+#[cfg(not(feature = "offline"))]
 #[embassy_executor::task]
 async fn cyw43_task(runner: cyw43::Runner<'static, Output<'static>, PioSpi<'static, PIO0, 0, DMA_CH0>>) -> ! {
     runner.run().await
 }
 
+/// Blinks the onboard LED as a heartbeat, and rejoins the WiFi whenever the
+/// network is lost, e.g. after the access point reboots or the lease expires.
+#[cfg(not(feature = "offline"))]
+#[embassy_executor::task]
+async fn wifi_task(
+    mut control: cyw43::Control<'static>,
+    stack: Stack<'static>,
+    mut network: usize,
+) -> ! {
+    loop {
+        // The heartbeat keeps going while the network is usable
+        let ssid = WIFI_NETWORKS[network].0;
+        select(heartbeat(&mut control, ssid), network_lost(stack)).await;
+
+        warn!("Network lost, rejoining the WiFi");
+        control.leave().await;
+        network = join_wifi(&mut control, network).await;
+        // DHCP starts over by itself once the link is back up
+    }
+}
+
+/// Blinks the onboard LED, pausing every [`SIGNAL_INTERVAL`] to measure the
+/// strength of `ssid` while [`Page::Signal`] is shown, as the scan needs the
+/// WiFi chip the LED is wired to.
+#[cfg(not(feature = "offline"))]
+async fn heartbeat(control: &mut cyw43::Control<'_>, ssid: &str) -> ! {
+    loop {
+        select(
+            blink_led_forever(&mut *control, HEARTBEAT_ON, HEARTBEAT_OFF),
+            Timer::after(SIGNAL_INTERVAL),
+        )
+        .await;
+
+        let rssi = if APP_STATE.lock().await.page == Page::Signal {
+            control.set(false).await;
+            signal_strength(control, ssid).await
+        } else {
+            None
+        };
+        APP_STATE.lock().await.rssi = rssi;
+    }
+}
+
+/// Scans for `ssid`, returning the strongest signal it is heard with in dBm,
+/// `None` if it isn't.
+#[cfg(not(feature = "offline"))]
+async fn signal_strength(control: &mut cyw43::Control<'_>, ssid: &str) -> Option<i16> {
+    let mut options = ScanOptions::default();
+    options.ssid = Some(String::try_from(ssid).ok()?);
+    let mut scanner = control.scan(options).await;
+    let mut strongest = None;
+    while let Some(bss) = scanner.next().await {
+        if bss.ssid[..bss.ssid_len as usize] == *ssid.as_bytes() {
+            strongest = strongest.max(Some(bss.rssi));
+        }
+    }
+    strongest
+}
+
+/// How many of the [`SIGNAL_LEVELS`] bars `rssi`, in dBm, lights up.
+fn signal_bars(rssi: i16) -> usize {
+    SIGNAL_LEVELS.iter().filter(|&&level| rssi >= level).count()
+}
+
+/// Joins the first network of [`WIFI_NETWORKS`] that accepts us, starting at
+/// index `first`, and returns its index.
+#[cfg(not(feature = "offline"))]
+async fn join_wifi(control: &mut cyw43::Control<'_>, first: usize) -> usize {
+    let mut network = first;
+    loop {
+        let (ssid, password) = WIFI_NETWORKS[network];
+        info!("joining {}...", ssid);
+        match control
+            .join(ssid, JoinOptions::new(password.as_bytes()))
+            .await
+        {
+            Ok(_) => return network,
+            Err(err) => {
+                info!("join {} failed with status={}", ssid, err.status);
+                network = (network + 1) % WIFI_NETWORKS.len();
+            }
+        }
+    }
+}
+
+/// Resolves once the link or the IPv4 configuration has been missing for
+/// [`NETWORK_LOSS_TIMEOUT`].
+#[cfg(not(feature = "offline"))]
+async fn network_lost(stack: Stack<'_>) {
+    let mut lost_for = Duration::from_ticks(0);
+    while lost_for < NETWORK_LOSS_TIMEOUT {
+        Timer::after(NETWORK_CHECK_INTERVAL).await;
+        if stack.is_link_up() && stack.is_config_up() {
+            lost_for = Duration::from_ticks(0);
+        } else {
+            lost_for += NETWORK_CHECK_INTERVAL;
+        }
+    }
+}
+
+#[cfg(not(feature = "offline"))]
 #[embassy_executor::task]
 async fn net_task(mut runner: embassy_net::Runner<'static, cyw43::NetDriver<'static>>) -> ! {
     runner.run().await
 }
 
-#[derive(Deserialize)]
-struct ApiDate {
-    year: u16,
-    month: u16,
-    day: u16,
+/// How the clock gets its IP address.
+#[cfg(not(feature = "offline"))]
+#[allow(dead_code)] // only the selected config is ever constructed
+enum NetworkConfig {
+    Dhcp,
+    Static {
+        address: Ipv4Cidr,
+        gateway: Ipv4Address,
+    },
 }
 
-#[derive(Deserialize)]
-struct ApiTime {
-    hour: u16,
-    minite: u16,
-    second: u16
+#[cfg(not(feature = "offline"))]
+impl NetworkConfig {
+    fn stack_config(&self) -> embassy_net::Config {
+        match *self {
+            NetworkConfig::Dhcp => embassy_net::Config::dhcpv4(Default::default()),
+            NetworkConfig::Static { address, gateway } => {
+                embassy_net::Config::ipv4_static(embassy_net::StaticConfigV4 {
+                    address,
+                    dns_servers: Vec::new(),
+                    gateway: Some(gateway),
+                })
+            }
+        }
+    }
 }
 
-#[derive(Deserialize)]
-struct ApiResponse {
-    time: ApiTime,
-    date: ApiDate,
+/// Formats the pressure in hPa and the altitude in meters, padded to a fixed
+/// width so a shorter value clears the previous one.
+fn format_barometer(barometer: Option<Barometer>) -> (String<16>, String<16>) {
+    let mut pressure = String::new();
+    let mut altitude = String::new();
+    match barometer {
+        Some(barometer) => {
+            let _ = core::write!(pressure, "{:>6.1} hPa", barometer.pressure_pa / 100.0);
+            let _ = core::write!(altitude, "{:>6.0} m", barometer.altitude_m);
+        }
+        None => {
+            let _ = pressure.push_str("  --.- hPa");
+            let _ = altitude.push_str("    -- m");
+        }
+    }
+    (pressure, altitude)
 }
 
-bind_interrupts!(struct Irqs {
-    PIO0_IRQ_0 => InterruptHandler<PIO0>;
-});
+/// Formats the time since the last sync with the time server as e.g.
+/// "synced 3m ago", `None` if it never succeeded.
+fn format_sync_age(age: Option<Duration>) -> String<16> {
+    let mut s = String::new();
+    let _ = match age.map(|age| age.as_secs()) {
+        None => core::write!(s, "not synced"),
+        Some(secs) if secs < 60 => core::write!(s, "synced {}s ago", secs),
+        Some(secs) if secs < 60 * 60 => core::write!(s, "synced {}m ago", secs / 60),
+        Some(secs) => core::write!(s, "synced {}h ago", secs / (60 * 60)),
+    };
+    s
+}
 
-#[embassy_executor::main]
-async fn main(spawner: Spawner) {
-    let peripherals = embassy_rp::init(Default::default());
+/// Formats a brightness as e.g. "70%".
+fn format_brightness(percent: u8) -> String<16> {
+    let mut s = String::new();
+    let _ = core::write!(s, "{}%", percent);
+    s
+}
 
-    info!("Initializing display...");
-
-    // ************** Display initialization - DO NOT MODIFY! *****************
-    let miso = peripherals.PIN_4;
-    let display_cs = peripherals.PIN_17;
-    let mosi = peripherals.PIN_19;
-    let clk = peripherals.PIN_18;
-    let rst = peripherals.PIN_0;
-    let dc = peripherals.PIN_16;
-    let mut display_config = embassy_rp::spi::Config::default();
-    display_config.frequency = DISPLAY_FREQ;
-    display_config.phase = embassy_rp::spi::Phase::CaptureOnSecondTransition;
-    display_config.polarity = embassy_rp::spi::Polarity::IdleHigh;
-
-    // Init SPI
-    let spi: embassy_rp::spi::Spi<'_, _, embassy_rp::spi::Blocking> =
-        embassy_rp::spi::Spi::new_blocking(
-            peripherals.SPI0,
-            clk,
-            mosi,
-            miso,
-            display_config.clone(),
-        );
-    let spi_bus: embassy_sync::blocking_mutex::Mutex<
-        embassy_sync::blocking_mutex::raw::NoopRawMutex,
-        _,
-    > = embassy_sync::blocking_mutex::Mutex::new(core::cell::RefCell::new(spi));
-
-    let display_spi = embassy_embedded_hal::shared_bus::blocking::spi::SpiDeviceWithConfig::new(
-        &spi_bus,
-        embassy_rp::gpio::Output::new(display_cs, embassy_rp::gpio::Level::High),
-        display_config,
-    );
+/// Temperature range mapped onto the RGB LED colors.
+#[derive(Clone, Copy)]
+struct Thresholds {
+    min: Threshold,
+    max: Threshold,
+}
 
-    let dc = embassy_rp::gpio::Output::new(dc, embassy_rp::gpio::Level::Low);
-    let rst = embassy_rp::gpio::Output::new(rst, embassy_rp::gpio::Level::Low);
-    let di = SPIDeviceInterface::new(display_spi, dc);
+impl Thresholds {
+    /// EEPROM representation, little endian minimum then maximum.
+    fn to_bytes(self) -> [u8; 4] {
+        let [min0, min1] = self.min.0.to_le_bytes();
+        let [max0, max1] = self.max.0.to_le_bytes();
+        [min0, min1, max0, max1]
+    }
 
-    // Init ST7789 LCD
-    let mut display = st7789::ST7789::new(di, rst, 240, 240);
-    display.init(&mut embassy_time::Delay).unwrap();
-    display
-        .set_orientation(st7789::Orientation::Portrait)
-        .unwrap();
-    display.clear(<embedded_graphics::pixelcolor::Rgb565 as embedded_graphics::pixelcolor::RgbColor>::BLACK).unwrap();
-    // ************************************************************************
-
-    info!("Display initialization finished!");
-
-    let fw = unsafe { core::slice::from_raw_parts(0x10100000 as *const u8, 230321) };
-    let clm = unsafe { core::slice::from_raw_parts(0x10140000 as *const u8, 4752) };
-
-    let pwr = Output::new(peripherals.PIN_23, Level::Low);
-    let cs = Output::new(peripherals.PIN_25, Level::High);
-    let mut pio = Pio::new(peripherals.PIO0, Irqs);
-    let spi = PioSpi::new(
-        &mut pio.common,
-        pio.sm0,
-        pio.irq0,
-        cs,
-        peripherals.PIN_24,
-        peripherals.PIN_29,
-        peripherals.DMA_CH0,
-    );
+    /// Parses the EEPROM representation, `None` if nothing valid was saved.
+    fn from_bytes(bytes: [u8; 4]) -> Option<Self> {
+        // Erased EEPROM
+        if bytes == [0xFF; 4] {
+            return None;
+        }
 
-    static STATE: StaticCell<cyw43::State> = StaticCell::new();
-    let state = STATE.init(cyw43::State::new());
-    let (net_device, mut control, runner) = cyw43::new(state, pwr, spi, fw).await;
-    spawner.spawn(cyw43_task(runner));
+        let min = Threshold(i16::from_le_bytes([bytes[0], bytes[1]]));
+        let max = Threshold(i16::from_le_bytes([bytes[2], bytes[3]]));
+        (min <= max).then_some(Thresholds { min, max })
+    }
+}
 
-    control.init(clm).await;
-    control
-        .set_power_management(cyw43::PowerManagementMode::PowerSave)
-        .await;
+/// Everything kept in the EEPROM across restarts.
+#[derive(Clone, Copy)]
+struct Settings {
+    thresholds: Thresholds,
+    /// Display and LED brightness, in percent
+    brightness: u8,
+    unit: TemperatureUnit,
+    time_format: TimeFormat,
+}
 
-    let config = embassy_net::Config::ipv4_static(embassy_net::StaticConfigV4 {
-        address: Ipv4Cidr::new(Ipv4Address::new(192, 168, 1, 9), 24),
-        dns_servers: Vec::new(),
-        gateway: Some(Ipv4Address::new(192, 168, 1, 1)),
-    });
-
-    // Generate random seed
-    let seed = 69;
-
-    // Init network stack
-    static RESOURCES: StaticCell<StackResources<5>> = StaticCell::new();
-    let (stack, runner) = embassy_net::new(
-        net_device,
-        config,
-        RESOURCES.init(StackResources::new()),
-        seed,
-    );
+impl Settings {
+    /// Size of the EEPROM representation.
+    const LEN: usize = 6;
+
+    const DEFAULT: Settings = Settings {
+        thresholds: DEFAULT_THRESHOLDS,
+        brightness: DEFAULT_BRIGHTNESS,
+        unit: DEFAULT_TEMPERATURE_UNIT,
+        time_format: DEFAULT_TIME_FORMAT,
+    };
+
+    /// EEPROM representation, the thresholds followed by the brightness and
+    /// a byte of flags, bit 0 set for Fahrenheit and bit 1 for 12 hour time.
+    fn to_bytes(self) -> [u8; Self::LEN] {
+        let [b0, b1, b2, b3] = self.thresholds.to_bytes();
+        let flags = (self.unit == TemperatureUnit::Fahrenheit) as u8
+            | ((self.time_format == TimeFormat::H12) as u8) << 1;
+        [b0, b1, b2, b3, self.brightness, flags]
+    }
+
+    /// Parses the EEPROM representation, falling back to the defaults for
+    /// whatever wasn't validly saved, e.g. the brightness when only the
+    /// thresholds were written by an older firmware.
+    fn from_bytes(bytes: [u8; Self::LEN]) -> Self {
+        let [b0, b1, b2, b3, brightness, flags] = bytes;
+        let (unit, time_format) = match flags {
+            // Erased EEPROM
+            0xFF => (DEFAULT_TEMPERATURE_UNIT, DEFAULT_TIME_FORMAT),
+            flags => (
+                if flags & 0b01 != 0 {
+                    TemperatureUnit::Fahrenheit
+                } else {
+                    TemperatureUnit::Celsius
+                },
+                if flags & 0b10 != 0 {
+                    TimeFormat::H12
+                } else {
+                    TimeFormat::H24
+                },
+            ),
+        };
+        Settings {
+            thresholds: Thresholds::from_bytes([b0, b1, b2, b3]).unwrap_or(DEFAULT_THRESHOLDS),
+            brightness: if brightness <= 100 {
+                brightness
+            } else {
+                DEFAULT_BRIGHTNESS
+            },
+            unit,
+            time_format,
+        }
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings::DEFAULT
+    }
+}
+
+/// Where the temperature was last seen relative to the thresholds.
+#[derive(Clone, Copy, PartialEq)]
+enum Band {
+    Cold,
+    InRange,
+    Hot,
+}
+
+impl Band {
+    /// The band `temp_c` falls in, coming from this one. Leaving it only takes
+    /// crossing a threshold by more than [`HYSTERESIS`].
+    fn next(self, temp_c: f64, thresholds: Thresholds) -> Band {
+        let min = thresholds.min.celsius();
+        let max = thresholds.max.celsius();
+        match self {
+            Band::Cold if temp_c > min + HYSTERESIS => Band::InRange,
+            Band::Hot if temp_c < max - HYSTERESIS => Band::InRange,
+            Band::InRange if temp_c < min - HYSTERESIS => Band::Cold,
+            Band::InRange if temp_c > max + HYSTERESIS => Band::Hot,
+            band => band,
+        }
+    }
+}
+
+/// Maps a temperature onto the LED color, from blue at the minimum threshold
+/// to red at the maximum, `band` being updated on the way. Past a threshold
+/// the LED turns cyan or yellow, as green is never part of the range.
+fn temp_to_rgb(temp_c: f64, thresholds: Thresholds, band: &mut Band) -> (u16, u16, u16) {
+    *band = band.next(temp_c, thresholds);
+    match *band {
+        Band::Cold => (0, 255, 255),
+        Band::Hot => (255, 255, 0),
+        Band::InRange => fraction_to_rgb(range_fraction(
+            temp_c,
+            thresholds.min.celsius(),
+            thresholds.max.celsius(),
+        )),
+    }
+}
+
+/// Where `temp_c` sits between `min` and `max`, from 0 to 1.
+fn range_fraction(temp_c: f64, min: f64, max: f64) -> f64 {
+    if max > min {
+        ((temp_c - min) / (max - min)).clamp(0.0, 1.0)
+    } else {
+        0.5
+    }
+}
+
+/// Blue at 0 to red at 1.
+fn fraction_to_rgb(t: f64) -> (u16, u16, u16) {
+    let red = (255.0 * t) as u16;
+    (red, 0, 255 - red)
+}
+
+/// Which screen the clock is on, the menu being entered with the A button.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Mode {
+    Clock,
+    /// Moving between the menu entries, this one highlighted
+    Menu(MenuItem),
+    /// Changing the value of this entry
+    Edit(MenuItem),
+}
+
+/// Entries of the settings menu.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum MenuItem {
+    Minimum,
+    Maximum,
+    Unit,
+    TimeFormat,
+    Brightness,
+    Exit,
+}
+
+impl MenuItem {
+    /// In the order they are listed.
+    const ALL: [MenuItem; 6] = [
+        MenuItem::Minimum,
+        MenuItem::Maximum,
+        MenuItem::Unit,
+        MenuItem::TimeFormat,
+        MenuItem::Brightness,
+        MenuItem::Exit,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            MenuItem::Minimum => "Minimum",
+            MenuItem::Maximum => "Maximum",
+            MenuItem::Unit => "Unit",
+            MenuItem::TimeFormat => "Time",
+            MenuItem::Brightness => "Brightness",
+            MenuItem::Exit => "Save and exit",
+        }
+    }
+
+    /// The current value of this entry in `settings`, empty if it has none.
+    fn value(self, settings: &Settings) -> String<16> {
+        match self {
+            MenuItem::Minimum => format_threshold(settings.thresholds.min, settings.unit),
+            MenuItem::Maximum => format_threshold(settings.thresholds.max, settings.unit),
+            MenuItem::Unit => String::try_from(settings.unit.label()).unwrap(),
+            MenuItem::TimeFormat => String::try_from(settings.time_format.label()).unwrap(),
+            MenuItem::Brightness => format_brightness(settings.brightness),
+            MenuItem::Exit => String::new(),
+        }
+    }
+
+    fn index(self) -> usize {
+        MenuItem::ALL
+            .iter()
+            .position(|&item| item == self)
+            .unwrap_or(0)
+    }
+
+    /// The entry above, wrapping around to the last one.
+    fn previous(self) -> Self {
+        let len = MenuItem::ALL.len();
+        MenuItem::ALL[(self.index() + len - 1) % len]
+    }
+
+    /// The entry below, wrapping around to the first one.
+    fn next(self) -> Self {
+        MenuItem::ALL[(self.index() + 1) % MenuItem::ALL.len()]
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Button {
+    A,
+    X,
+    Y,
+}
+
+/// Button presses, waiting to be handled by the main loop.
+static BUTTONS: Channel<CriticalSectionRawMutex, Button, 4> = Channel::new();
+
+/// Every press, including those only waking the display up.
+static BUTTON_ACTIVITY: Signal<CriticalSectionRawMutex, ()> = Signal::new();
+
+/// Set while the display is blanked by [`idle_task`].
+static DISPLAY_ASLEEP: AtomicBool = AtomicBool::new(false);
 
-    spawner.spawn(net_task(runner));
+/// Turns the display back on (`true`) or blanks it (`false`).
+static DISPLAY_POWER: Signal<CriticalSectionRawMutex, bool> = Signal::new();
 
+#[embassy_executor::task]
+async fn buttons_task(mut a: Input<'static>, mut x: Input<'static>, mut y: Input<'static>) -> ! {
     loop {
-        match control
-            .join(WIFI_NETWORK, JoinOptions::new(WIFI_PASSWORD.as_bytes()))
-            .await
+        let button = match select3(
+            a.wait_for_falling_edge(),
+            x.wait_for_falling_edge(),
+            y.wait_for_falling_edge(),
+        )
+        .await
         {
-            Ok(_) => break,
-            Err(err) => {
-                info!("join failed with status={}", err.status);
-            }
+            Either3::First(_) => Button::A,
+            Either3::Second(_) => Button::X,
+            Either3::Third(_) => Button::Y,
+        };
+
+        send_press(button);
+        Timer::after(BUTTON_DEBOUNCE).await;
+    }
+}
+
+/// Turns of the optional rotary encoder, sent as X presses clockwise and Y
+/// presses counterclockwise so it moves through the menu and adjusts the
+/// values like the buttons do. Its state table needs no debounce delay.
+#[embassy_executor::task]
+async fn encoder_task(mut encoder: Encoder<'static>) -> ! {
+    loop {
+        let button = match encoder.step().await {
+            Direction::Clockwise => Button::X,
+            Direction::CounterClockwise => Button::Y,
+        };
+        send_press(button);
+    }
+}
+
+/// Hands a press over to the main loop, unless it only wakes the display up.
+fn send_press(button: Button) {
+    BUTTON_ACTIVITY.signal(());
+    // The press waking the display up isn't acted upon, and drop it rather
+    // than block if the main loop falls behind
+    if !DISPLAY_ASLEEP.load(Ordering::Relaxed) {
+        let _ = BUTTONS.try_send(button);
+    }
+}
+
+/// Blanks the display after [`DISPLAY_TIMEOUT`] without a button press, and
+/// wakes it up on the next one.
+#[embassy_executor::task]
+async fn idle_task() -> ! {
+    loop {
+        let idle = select(BUTTON_ACTIVITY.wait(), Timer::after(DISPLAY_TIMEOUT)).await;
+        if let Either::First(()) = idle {
+            continue;
         }
+
+        DISPLAY_ASLEEP.store(true, Ordering::Relaxed);
+        DISPLAY_POWER.signal(false);
+        BUTTON_ACTIVITY.wait().await;
+        DISPLAY_ASLEEP.store(false, Ordering::Relaxed);
+        DISPLAY_POWER.signal(true);
     }
+}
 
-    // Wait for DHCP, not necessary when using static IP
-    info!("waiting for DHCP...");
-    while !stack.is_config_up() {
-        Timer::after_millis(100).await;
+/// Whether X and Y are held down from boot for [`FACTORY_RESET_HOLD`],
+/// asking on `display` to keep holding them meanwhile. `show` is called once
+/// a screen is drawn, for displays that buffer it.
+async fn factory_reset_requested<D>(
+    x: &Input<'_>,
+    y: &Input<'_>,
+    display: &mut D,
+    mut show: impl FnMut(&mut D),
+) -> bool
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let held = || x.is_low() && y.is_low();
+    if !held() {
+        return false;
     }
-    info!("DHCP is now up!");
 
-    info!("waiting for link up...");
-    while !stack.is_link_up() {
-        Timer::after_millis(500).await;
+    draw_notice(display, "Factory reset", "Keep holding X and Y...");
+    show(display);
+    let start = Instant::now();
+    while start.elapsed() < FACTORY_RESET_HOLD {
+        if !held() {
+            display.clear(D::Color::BACKGROUND).unwrap();
+            show(display);
+            return false;
+        }
+        Timer::after(BUTTON_DEBOUNCE).await;
     }
-    info!("Link is up!");
+    true
+}
 
-    info!("waiting for stack to be up...");
-    stack.wait_config_up().await;
-    info!("Stack is up!");
-    let mut rx_buffer = [0; 8192];
-    let mut tls_read_buffer = [0; 16640];
-    let mut tls_write_buffer = [0; 16640];
+/// Fake temperature for the offline demo, sweeping back and forth across the
+/// default thresholds about once a minute.
+#[cfg(feature = "offline")]
+fn simulated_temperature() -> f64 {
+    let phase = (embassy_time::Instant::now().as_secs() % 60) as f64 / 60.0;
+    let triangle = 1.0 - (2.0 * phase - 1.0).abs();
+    16.0 + 12.0 * triangle
+}
 
-    let client_state = TcpClientState::<1, 1024, 1024>::new();
-    let tcp_client = TcpClient::new(stack, &client_state);
-    let dns_client = DnsSocket::new(stack);
-    let tls_config = TlsConfig::new(
-        seed,
-        &mut tls_read_buffer,
-        &mut tls_write_buffer,
-        TlsVerify::None,
-    );
+/// The RP2040 on-die temperature sensor, far less accurate than the BMP280 and
+/// skewed by the chip heating up, but better than nothing.
+#[cfg(not(feature = "offline"))]
+struct InternalSensor {
+    adc: Adc<'static, embassy_rp::adc::Blocking>,
+    channel: AdcChannel<'static>,
+}
 
-    let mut http_client = HttpClient::new(&tcp_client, &dns_client);
-    let url = "http://192.168.1.199:5000/time";
+#[cfg(not(feature = "offline"))]
+impl Sensor for InternalSensor {
+    /// Temperature in degrees Celsius
+    type Reading = f64;
+    type Error = embassy_rp::adc::Error;
 
-    info!("connecting to {}", &url);
+    fn read(&mut self) -> Result<f64, Self::Error> {
+        let raw = self.adc.blocking_read(&mut self.channel)?;
 
-    let mut request = match http_client.request(Method::GET, &url).await {
-        Ok(req) => req,
-        Err(e) => {
-            error!("Failed to make HTTP request: {:?}", e);
-            return; // handle the error
-        }
-    };
+        // From the RP2040 datasheet, with a 3.3 V reference
+        let voltage = raw as f64 * 3.3 / 4096.0;
+        Ok(27.0 - (voltage - 0.706) / 0.001721)
+    }
+}
+
+/// Pressure reading of the BMP280, with the altitude it works out to.
+#[derive(Clone, Copy)]
+struct Barometer {
+    pressure_pa: f64,
+    altitude_m: f64,
+}
 
-    let response = match request.send(&mut rx_buffer).await {
-        Ok(resp) => resp,
+/// Reads the temperature from any sensor whose readings are BMP280 measurements.
+#[cfg(not(feature = "offline"))]
+fn read_temperature<S: Sensor<Reading = Measurement>>(sensor: &mut S) -> Option<f64> {
+    match sensor.read() {
+        Ok(measurement) => Some(measurement.temperature_c),
         Err(_e) => {
-            error!("Failed to send HTTP request");
-            return; // handle the error;
+            warn!("Failed to read the temperature");
+            None
         }
-    };
+    }
+}
 
-    let body = match from_utf8(response.body().read_to_end().await.unwrap()) {
-        Ok(b) => b,
-        Err(_e) => {
-            error!("Failed to read response body");
-            return; // handle the error
+/// The temperature sources, the on-die sensor standing in for the BMP280
+/// when it can't be read.
+struct TemperatureSensors {
+    #[cfg(not(feature = "offline"))]
+    bmp: BMP280<I2cDevice<'static, NoopRawMutex, I2c<'static, I2C0, embassy_rp::i2c::Blocking>>>,
+    #[cfg(not(feature = "offline"))]
+    internal: InternalSensor,
+}
+
+impl TemperatureSensors {
+    /// Temperature in degrees Celsius, `None` if no sensor could be read.
+    fn read(&mut self) -> Option<f64> {
+        #[cfg(not(feature = "offline"))]
+        return read_temperature(&mut self.bmp).or_else(|| self.internal.read().ok());
+        #[cfg(feature = "offline")]
+        return Some(simulated_temperature());
+    }
+
+    /// Pressure and altitude, `None` if the BMP280, the only sensor measuring
+    /// pressure, can't be read.
+    fn read_barometer(&mut self) -> Option<Barometer> {
+        #[cfg(feature = "offline")]
+        return None;
+        #[cfg(not(feature = "offline"))]
+        {
+            let pressure_pa = self.bmp.measure().ok()?.pressure_pa;
+            Some(Barometer {
+                pressure_pa,
+                altitude_m: altitude_from_pressure(pressure_pa, SEA_LEVEL_PRESSURE),
+            })
         }
-    };
-    info!("Response body: {:?}", &body);
+    }
+}
 
-    // parse the response body and update the RTC
+/// Mean of the last `N` values pushed.
+struct MovingAverage<const N: usize> {
+    values: [f64; N],
+    len: usize,
+    next: usize,
+}
 
-    let bytes = body.as_bytes();
-    match serde_json_core::de::from_slice::<ApiResponse>(bytes) {
-        Ok((output, _used)) => {
-            info!("Datetime: {:?}", output.date.day);
+impl<const N: usize> MovingAverage<N> {
+    const fn new() -> Self {
+        MovingAverage {
+            values: [0.0; N],
+            len: 0,
+            next: 0,
         }
-        Err(_e) => {
-            error!("Failed to parse response body");
-            return; // handle the error
+    }
+
+    /// Adds `value`, replacing the oldest one once full, and returns the
+    /// mean.
+    fn push(&mut self, value: f64) -> f64 {
+        if N == 0 {
+            return value;
         }
+
+        self.values[self.next] = value;
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+        self.values[..self.len].iter().sum::<f64>() / self.len as f64
     }
 
-    Timer::after(Duration::from_secs(5)).await;
+    fn clear(&mut self) {
+        self.len = 0;
+        self.next = 0;
+    }
+}
 
-    // Write welcome message
-    let style = MonoTextStyle::new(&FONT_7X13_BOLD, Rgb565::CYAN);
-    Text::new("Welcome to Rust Workshop!", Point::new(36, 190), style)
-        .draw(&mut display)
-        .unwrap();
+/// Band the temperature just crossed into, sounded by [`buzzer_task`].
+static ALARM: Signal<CriticalSectionRawMutex, Band> = Signal::new();
+
+/// Latest temperature from [`sensor_task`], in degrees Celsius.
+static TEMPERATURE: Watch<CriticalSectionRawMutex, Option<f64>, 1> = Watch::new();
 
-    // Wait a bit
-    Timer::after_secs(10).await;
+/// Latest pressure reading from [`sensor_task`].
+static BAROMETER: Watch<CriticalSectionRawMutex, Option<Barometer>, 1> = Watch::new();
+
+/// Samples the temperature every [`SENSOR_INTERVAL`], whatever the rest of the
+/// clock is busy with, and publishes the mean of the last
+/// [`TEMPERATURE_SMOOTHING`] readings, along with the pressure. Crossing out
+/// of the thresholds raises the [`ALARM`].
+#[embassy_executor::task]
+async fn sensor_task(mut sensors: TemperatureSensors) -> ! {
+    let sender = TEMPERATURE.sender();
+    let barometer = BAROMETER.sender();
+    let mut average = MovingAverage::<TEMPERATURE_SMOOTHING>::new();
+    let mut band = Band::InRange;
+    loop {
+        let temperature = match sensors.read() {
+            Some(temperature) => Some(average.push(temperature)),
+            // Start over rather than average across the outage
+            None => {
+                average.clear();
+                None
+            }
+        };
+        if let Some(temperature) = temperature {
+            let thresholds = APP_STATE.lock().await.settings.thresholds;
+            let crossed = band.next(temperature, thresholds);
+            if crossed != band && crossed != Band::InRange {
+                ALARM.signal(crossed);
+            }
+            band = crossed;
+        }
+        sender.send(temperature);
+        barometer.send(sensors.read_barometer());
+        Timer::after(SENSOR_INTERVAL).await;
+    }
+}
 
-    // Clear display
-    display.clear(Rgb565::BLACK).unwrap();
+/// Beeps [`ALARM_BEEPS`] times whenever the [`ALARM`] is raised, high pitched
+/// for too hot and low pitched for too cold.
+#[embassy_executor::task]
+async fn buzzer_task(mut buzzer: Buzzer<'static>) -> ! {
     loop {
-        Timer::after_secs(1).await;
+        let freq_hz = match ALARM.wait().await {
+            Band::Hot => ALARM_HOT_HZ,
+            Band::Cold => ALARM_COLD_HZ,
+            Band::InRange => continue,
+        };
+        for _ in 0..ALARM_BEEPS {
+            buzzer.beep(freq_hz, ALARM_BEEP).await;
+            Timer::after(ALARM_BEEP).await;
+        }
+    }
+}
+
+type I2cBus = embassy_sync::blocking_mutex::Mutex<
+    NoopRawMutex,
+    RefCell<I2c<'static, I2C0, embassy_rp::i2c::Blocking>>,
+>;
+type DisplaySpiBus =
+    embassy_sync::blocking_mutex::Mutex<NoopRawMutex, RefCell<Spi<'static, SPI0, Blocking>>>;
+type Display = st7789::ST7789<
+    SPIDeviceInterface<
+        SpiDeviceWithConfig<'static, NoopRawMutex, Spi<'static, SPI0, Blocking>, Output<'static>>,
+        Output<'static>,
+    >,
+    Output<'static>,
+>;
+
+/// SSD1306 OLED sharing the I2C bus with the BMP280, in place of the Pico
+/// Explorer display.
+#[cfg(feature = "ssd1306")]
+type Oled = Ssd1306<
+    I2CInterface<I2cDevice<'static, NoopRawMutex, I2c<'static, I2C0, embassy_rp::i2c::Blocking>>>,
+    DisplaySize128x64,
+    BufferedGraphicsMode<DisplaySize128x64>,
+>;
+
+/// The panel the screens are rendered on, picked at boot.
+enum Panel {
+    Explorer(Display),
+    /// Kept in a static, its frame buffer being a lot bigger than the rest
+    #[cfg(feature = "ssd1306")]
+    Oled(&'static mut Oled),
+}
+
+/// Set once the OLED is in use, for the panic handler to pick the same panel.
+#[cfg(feature = "ssd1306")]
+static OLED_IN_USE: AtomicBool = AtomicBool::new(false);
+
+/// Sets up the OLED, or warns and returns `None` so the clock falls back to
+/// the Pico Explorer display when it doesn't answer.
+#[cfg(feature = "ssd1306")]
+fn init_oled(i2c_bus: &'static I2cBus) -> Option<Panel> {
+    let interface = I2CDisplayInterface::new(I2cDevice::new(i2c_bus));
+    let mut oled = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
+        .into_buffered_graphics_mode();
+    match oled.init() {
+        Ok(()) => {
+            static OLED: StaticCell<Oled> = StaticCell::new();
+            OLED_IN_USE.store(true, Ordering::Relaxed);
+            Some(Panel::Oled(OLED.init(oled)))
+        }
+        Err(_e) => {
+            warn!("No OLED found, falling back to the Pico Explorer display");
+            None
+        }
+    }
+}
+
+/// Sends the frame buffer, which the OLED only shows once flushed.
+#[cfg(feature = "ssd1306")]
+fn flush(oled: &mut Oled) {
+    if let Err(_e) = oled.flush() {
+        warn!("Failed to send the frame to the OLED");
+    }
+}
+
+impl Panel {
+    /// [`factory_reset_requested`] on this panel.
+    async fn factory_reset_requested(&mut self, x: &Input<'_>, y: &Input<'_>) -> bool {
+        match self {
+            Panel::Explorer(display) => factory_reset_requested(x, y, display, |_| {}).await,
+            #[cfg(feature = "ssd1306")]
+            Panel::Oled(oled) => factory_reset_requested(x, y, *oled, flush).await,
+        }
+    }
+
+    /// [`draw_notice`] on this panel.
+    fn notice(&mut self, title: &str, detail: &str) {
+        match self {
+            Panel::Explorer(display) => draw_notice(display, title, detail),
+            #[cfg(feature = "ssd1306")]
+            Panel::Oled(oled) => {
+                draw_notice(*oled, title, detail);
+                flush(oled);
+            }
+        }
+    }
+
+    /// Blanks the panel.
+    fn clear(&mut self) {
+        match self {
+            Panel::Explorer(display) => display.clear(Rgb565::BACKGROUND).unwrap(),
+            #[cfg(feature = "ssd1306")]
+            Panel::Oled(oled) => {
+                DrawTarget::clear(*oled, BinaryColor::BACKGROUND).unwrap();
+                flush(oled);
+            }
+        }
+    }
+}
+
+/// What the clock screen shows below the time, switched with X and Y.
+#[derive(Clone, Copy, PartialEq)]
+enum Page {
+    Temperature,
+    Barometer,
+    /// WiFi signal strength, to find a spot with reliable connectivity
+    Signal,
+}
+
+impl Page {
+    fn previous(self) -> Self {
+        match self {
+            Page::Temperature => Page::Signal,
+            Page::Barometer => Page::Temperature,
+            Page::Signal => Page::Barometer,
+        }
+    }
+
+    fn next(self) -> Self {
+        match self {
+            Page::Temperature => Page::Barometer,
+            Page::Barometer => Page::Signal,
+            Page::Signal => Page::Temperature,
+        }
+    }
+}
+
+/// Everything shown on the clock screen.
+struct DisplayState {
+    time: Option<DateTime>,
+    temperature: Option<f64>,
+    barometer: Option<Barometer>,
+    page: Page,
+    /// WiFi signal strength in dBm, only measured on [`Page::Signal`]
+    rssi: Option<i16>,
+    status: &'static str,
+    /// Time since the RTC was last set from the time server
+    synced_ago: Option<Duration>,
+    /// The menu is drawn instead of the clock unless in [`Mode::Clock`]
+    mode: Mode,
+    settings: Settings,
+}
+
+/// Latest state to be rendered by [`display_task`].
+static DISPLAY_STATE: Signal<CriticalSectionRawMutex, DisplayState> = Signal::new();
+
+/// What the clock currently knows and shows, kept up to date by the main loop
+/// from the RTC, the sensors and the button presses.
+struct AppState {
+    time: Option<DateTime>,
+    temperature: Option<f64>,
+    barometer: Option<Barometer>,
+    settings: Settings,
+    mode: Mode,
+    page: Page,
+    /// Set by [`wifi_task`] while on [`Page::Signal`]
+    rssi: Option<i16>,
+    /// Hysteresis state of the LED color
+    band: Band,
+    /// When the RTC was last set from the time server
+    last_sync: Option<Instant>,
+}
+
+impl AppState {
+    const fn new() -> Self {
+        AppState {
+            time: None,
+            temperature: None,
+            barometer: None,
+            settings: Settings::DEFAULT,
+            mode: Mode::Clock,
+            page: Page::Temperature,
+            rssi: None,
+            band: Band::InRange,
+            last_sync: None,
+        }
+    }
+
+    /// The LED color for the current temperature, `None` if there is nothing
+    /// to show.
+    fn led_color(&mut self) -> Option<(u16, u16, u16)> {
+        let thresholds = self.settings.thresholds;
+        match (self.mode, self.temperature) {
+            // Preview the edited thresholds right away, with no hysteresis
+            // holding the previous color
+            (Mode::Edit(MenuItem::Minimum | MenuItem::Maximum), Some(temperature)) => {
+                Some(temp_to_rgb(temperature, thresholds, &mut Band::InRange))
+            }
+            // Nothing to preview against, show the end of the range being set
+            (Mode::Edit(MenuItem::Minimum), None) => Some(fraction_to_rgb(0.0)),
+            (Mode::Edit(MenuItem::Maximum), None) => Some(fraction_to_rgb(1.0)),
+            (_, Some(temperature)) => Some(temp_to_rgb(temperature, thresholds, &mut self.band)),
+            (_, None) => None,
+        }
+    }
+
+    /// What [`display_task`] should render, with `status` in the corner.
+    fn display_state(&self, status: &'static str) -> DisplayState {
+        DisplayState {
+            time: self.time.clone(),
+            temperature: self.temperature,
+            barometer: self.barometer,
+            page: self.page,
+            rssi: self.rssi,
+            status,
+            synced_ago: self.last_sync.map(|at| at.elapsed()),
+            mode: self.mode,
+            settings: self.settings,
+        }
+    }
+
+    /// Acts on a button press, returning the settings to save once leaving
+    /// the menu.
+    fn press(&mut self, button: Button) -> Option<Settings> {
+        let settings = &mut self.settings;
+        let thresholds = &mut settings.thresholds;
+        match (self.mode, button) {
+            (Mode::Clock, Button::A) => self.mode = Mode::Menu(MenuItem::ALL[0]),
+            (Mode::Clock, Button::X) => self.page = self.page.previous(),
+            (Mode::Clock, Button::Y) => self.page = self.page.next(),
+
+            (Mode::Menu(item), Button::X) => self.mode = Mode::Menu(item.previous()),
+            (Mode::Menu(item), Button::Y) => self.mode = Mode::Menu(item.next()),
+            (Mode::Menu(MenuItem::Unit), Button::A) => settings.unit = settings.unit.toggled(),
+            (Mode::Menu(MenuItem::TimeFormat), Button::A) => {
+                settings.time_format = settings.time_format.toggled()
+            }
+            (Mode::Menu(MenuItem::Exit), Button::A) => {
+                self.mode = Mode::Clock;
+                return Some(self.settings);
+            }
+            (Mode::Menu(item), Button::A) => self.mode = Mode::Edit(item),
+
+            (Mode::Edit(item), Button::A) => self.mode = Mode::Menu(item),
+            // Keep the minimum at or below the maximum
+            (Mode::Edit(MenuItem::Minimum), Button::X) => {
+                thresholds.min = thresholds.min.increment().min(thresholds.max)
+            }
+            (Mode::Edit(MenuItem::Minimum), Button::Y) => {
+                thresholds.min = thresholds.min.decrement()
+            }
+            (Mode::Edit(MenuItem::Maximum), Button::X) => {
+                thresholds.max = thresholds.max.increment()
+            }
+            (Mode::Edit(MenuItem::Maximum), Button::Y) => {
+                thresholds.max = thresholds.max.decrement().max(thresholds.min)
+            }
+            (Mode::Edit(MenuItem::Brightness), Button::X) => {
+                settings.brightness = settings.brightness.saturating_add(BRIGHTNESS_STEP).min(100)
+            }
+            (Mode::Edit(MenuItem::Brightness), Button::Y) => {
+                settings.brightness = settings.brightness.saturating_sub(BRIGHTNESS_STEP)
+            }
+            // The other entries are toggled rather than edited
+            (Mode::Edit(_), _) => {}
+        }
+        None
+    }
+}
+
+/// Shared by every task needing more than its own inputs.
+static APP_STATE: Mutex<CriticalSectionRawMutex, AppState> = Mutex::new(AppState::new());
+
+/// Boot progress shown on the splash screen.
+#[derive(Clone, Copy)]
+enum BootStep {
+    JoiningWifi,
+    WaitingForNetwork,
+    GettingTime,
+    Ready,
+}
+
+impl BootStep {
+    const ALL: [BootStep; 4] = [
+        BootStep::JoiningWifi,
+        BootStep::WaitingForNetwork,
+        BootStep::GettingTime,
+        BootStep::Ready,
+    ];
+
+    fn label(self) -> &'static str {
+        match self {
+            BootStep::JoiningWifi => "Joining WiFi...",
+            BootStep::WaitingForNetwork => "Waiting for network...",
+            BootStep::GettingTime => "Getting time...",
+            BootStep::Ready => "Ready!",
+        }
+    }
+}
+
+/// Latest boot step, shown until the first [`DISPLAY_STATE`] arrives.
+static BOOT_STEP: Signal<CriticalSectionRawMutex, BootStep> = Signal::new();
+
+/// Colors the screens are drawn with, so that they render on color and
+/// monochrome panels alike.
+trait ClockColor: PixelColor {
+    const BACKGROUND: Self;
+    const TEXT: Self;
+    const ACCENT: Self;
+    const DONE: Self;
+
+    /// The color at `percent` of its intensity.
+    fn dimmed(self, percent: u8) -> Self;
+
+    /// The closest color to 8 bit RGB channels, as sent to the LED.
+    fn from_rgb(rgb: (u16, u16, u16)) -> Self;
+}
+
+impl ClockColor for Rgb565 {
+    const BACKGROUND: Self = Rgb565::BLACK;
+    const TEXT: Self = Rgb565::WHITE;
+    const ACCENT: Self = Rgb565::CYAN;
+    const DONE: Self = Rgb565::GREEN;
+
+    fn dimmed(self, percent: u8) -> Self {
+        let scale = |channel: u8| (channel as u16 * percent.min(100) as u16 / 100) as u8;
+        Rgb565::new(scale(self.r()), scale(self.g()), scale(self.b()))
+    }
+
+    fn from_rgb((r, g, b): (u16, u16, u16)) -> Self {
+        let channel = |value: u16, bits: u32| (value.min(255) >> (8 - bits)) as u8;
+        Rgb565::new(channel(r, 5), channel(g, 6), channel(b, 5))
+    }
+}
+
+/// E.g. SSD1306 OLEDs. A pixel is either lit or not, so dimming can only
+/// turn it off.
+impl ClockColor for BinaryColor {
+    const BACKGROUND: Self = BinaryColor::Off;
+    const TEXT: Self = BinaryColor::On;
+    const ACCENT: Self = BinaryColor::On;
+    const DONE: Self = BinaryColor::On;
+
+    fn dimmed(self, percent: u8) -> Self {
+        if percent == 0 {
+            BinaryColor::Off
+        } else {
+            self
+        }
+    }
+
+    fn from_rgb(rgb: (u16, u16, u16)) -> Self {
+        BinaryColor::from(rgb != (0, 0, 0))
+    }
+}
+
+/// Draws the splash screen with every step up to `current`, laid out for
+/// the size of `display`.
+fn draw_splash<D>(display: &mut D, current: BootStep)
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let size = display.bounding_box().size;
+    let (width, height) = (size.width as i32, size.height as i32);
+    let title_style = MonoTextStyle::new(&FONT_7X13_BOLD, D::Color::ACCENT);
+    let done_style = MonoTextStyle::new(&FONT_7X13_BOLD, D::Color::DONE);
+    let current_style = MonoTextStyle::new(&FONT_7X13_BOLD, D::Color::TEXT);
+
+    Text::with_alignment(
+        "Welcome to Rust Workshop!",
+        Point::new(width / 2, height / 4),
+        title_style,
+        Alignment::Center,
+    )
+    .draw(display)
+    .unwrap();
+
+    let current = current as usize;
+    for (i, step) in BootStep::ALL.iter().take(current + 1).enumerate() {
+        let style = if i < current {
+            done_style
+        } else {
+            current_style
+        };
+        let position = Point::new(20, height * 5 / 12 + 20 * i as i32);
+        Text::new(step.label(), position, style)
+            .draw(display)
+            .unwrap();
+    }
+
+    // Progress bar
+    let bar = Rectangle::new(
+        Point::new(20, height * 19 / 24),
+        Size::new(size.width.saturating_sub(40), 10),
+    );
+    bar.into_styled(PrimitiveStyle::with_stroke(D::Color::TEXT, 1))
+        .draw(display)
+        .unwrap();
+    let filled = bar.size.width * (current as u32 + 1) / BootStep::ALL.len() as u32;
+    Rectangle::new(bar.top_left, Size::new(filled, bar.size.height))
+        .into_styled(PrimitiveStyle::with_fill(D::Color::DONE))
+        .draw(display)
+        .unwrap();
+}
+
+/// Replaces the whole screen with `title` and a line of `detail` below it.
+fn draw_notice<D>(display: &mut D, title: &str, detail: &str)
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let size = display.bounding_box().size;
+    let center = Point::new(size.width as i32 / 2, size.height as i32 / 2);
+    let title_style = MonoTextStyle::new(&FONT_10X20, D::Color::ACCENT);
+    let detail_style = MonoTextStyle::new(&FONT_7X13_BOLD, D::Color::TEXT);
+
+    display.clear(D::Color::BACKGROUND).unwrap();
+    Text::with_alignment(title, center, title_style, Alignment::Center)
+        .draw(display)
+        .unwrap();
+    Text::with_alignment(
+        detail,
+        center + Point::new(0, 30),
+        detail_style,
+        Alignment::Center,
+    )
+    .draw(display)
+    .unwrap();
+}
+
+/// Draws a horizontal gauge across `area`, filled in proportion to where
+/// `temp_c` sits between `min` and `max`, in the color the LED shows for it.
+fn draw_temp_gauge<D>(display: &mut D, area: Rectangle, temp_c: f64, min: f64, max: f64)
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let t = range_fraction(temp_c, min, max);
+    let color = D::Color::from_rgb(fraction_to_rgb(t));
+    let filled = (area.size.width as f64 * t) as u32;
+    let fill = Rectangle::new(area.top_left, Size::new(filled, area.size.height));
+    let rest = Rectangle::new(
+        area.top_left + Point::new(filled as i32, 0),
+        Size::new(area.size.width - filled, area.size.height),
+    );
+
+    fill.into_styled(PrimitiveStyle::with_fill(color))
+        .draw(display)
+        .unwrap();
+    // Clear what a higher temperature filled before
+    rest.into_styled(PrimitiveStyle::with_fill(D::Color::BACKGROUND))
+        .draw(display)
+        .unwrap();
+    area.into_styled(PrimitiveStyle::with_stroke(D::Color::TEXT, 1))
+        .draw(display)
+        .unwrap();
+}
+
+/// Draws [`SIGNAL_LEVELS`] bars of growing height across `area`, the first
+/// `lit` of them filled and the others only outlined.
+fn draw_signal_bars<D>(display: &mut D, area: Rectangle, lit: usize)
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let count = SIGNAL_LEVELS.len() as u32;
+    let pitch = area.size.width / count;
+    for i in 0..count {
+        let height = area.size.height * (i + 1) / count;
+        let bar = Rectangle::new(
+            area.top_left + Point::new((pitch * i) as i32, (area.size.height - height) as i32),
+            Size::new(pitch.saturating_sub(2), height),
+        );
+        let fill = if (i as usize) < lit {
+            D::Color::ACCENT
+        } else {
+            D::Color::BACKGROUND
+        };
+        let style = PrimitiveStyleBuilder::new()
+            .fill_color(fill)
+            .stroke_color(D::Color::TEXT)
+            .stroke_width(1)
+            .build();
+        bar.into_styled(style).draw(display).unwrap();
+    }
+}
+
+/// Draws the clock screen or, while configuring, the settings menu, laid out
+/// for the size of `display`.
+fn draw_clock<D>(display: &mut D, state: &DisplayState)
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let size = display.bounding_box().size;
+    let (width, height) = (size.width as i32, size.height as i32);
+    let time_position = Point::new(width / 2, height * 5 / 12);
+    let temperature_position = Point::new(width / 2, height * 7 / 12);
+    let status_position = Point::new(2, height - 10);
+    let settings = &state.settings;
+
+    // The backlight isn't wired to a GPIO, dim the text instead
+    let time_style = MonoTextStyleBuilder::new()
+        .font(&FONT_10X20)
+        .text_color(D::Color::TEXT.dimmed(settings.brightness))
+        .background_color(D::Color::BACKGROUND)
+        .build();
+    let status_style = MonoTextStyleBuilder::new()
+        .font(&FONT_7X13_BOLD)
+        .text_color(D::Color::ACCENT.dimmed(settings.brightness))
+        .background_color(D::Color::BACKGROUND)
+        .build();
+
+    if state.mode != Mode::Clock {
+        draw_menu(display, state.mode, settings);
+    } else {
+        if let Some(time) = &state.time {
+            let format = settings.time_format;
+            draw_time(display, time_position, time_style, time, format);
+        }
+
+        let below_position = Point::new(width / 2, height * 3 / 4);
+        match state.page {
+            Page::Temperature => {
+                let temp = match state.temperature {
+                    Some(temperature) => format_temperature(temperature, settings.unit),
+                    None => String::try_from("--.-    ").unwrap(),
+                };
+                Text::with_alignment(&temp, temperature_position, time_style, Alignment::Center)
+                    .draw(display)
+                    .unwrap();
+
+                if let Some(temperature) = state.temperature {
+                    let gauge = Rectangle::new(
+                        Point::new(20, height * 3 / 4),
+                        Size::new(size.width.saturating_sub(40), 10),
+                    );
+                    let (min, max) = (settings.thresholds.min, settings.thresholds.max);
+                    draw_temp_gauge(display, gauge, temperature, min.celsius(), max.celsius());
+                }
+            }
+            Page::Barometer => {
+                let (hpa, meters) = format_barometer(state.barometer);
+                Text::with_alignment(&hpa, temperature_position, time_style, Alignment::Center)
+                    .draw(display)
+                    .unwrap();
+                Text::with_alignment(&meters, below_position, time_style, Alignment::Center)
+                    .draw(display)
+                    .unwrap();
+            }
+            Page::Signal => {
+                let mut rssi: String<16> = String::new();
+                match state.rssi {
+                    Some(dbm) => {
+                        let _ = core::write!(rssi, "WiFi {:>4} dBm", dbm);
+                    }
+                    None => {
+                        let _ = rssi.push_str("WiFi   -- dBm");
+                    }
+                }
+                Text::with_alignment(&rssi, temperature_position, time_style, Alignment::Center)
+                    .draw(display)
+                    .unwrap();
+
+                let bars = Rectangle::new(below_position - Point::new(30, 10), Size::new(60, 30));
+                draw_signal_bars(display, bars, state.rssi.map_or(0, signal_bars));
+            }
+        }
+    }
+
+    let synced = format_sync_age(state.synced_ago);
+    let mut line: String<34> = String::new();
+    let _ = core::write!(line, "{}, {}", state.status, synced);
+    // Pad to clear any longer previous status
+    let mut status: String<34> = String::new();
+    let _ = core::write!(status, "{:<33}", line);
+    Text::new(&status, status_position, status_style)
+        .draw(display)
+        .unwrap();
+}
+
+/// Lists the menu entries with their values, highlighting the one `mode`
+/// is on and bracketing its value while it is edited.
+fn draw_menu<D>(display: &mut D, mode: Mode, settings: &Settings)
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let size = display.bounding_box().size;
+    let (width, height) = (size.width as i32, size.height as i32);
+    let style = |color: D::Color| {
+        MonoTextStyleBuilder::new()
+            .font(&FONT_7X13_BOLD)
+            .text_color(color.dimmed(settings.brightness))
+            .background_color(D::Color::BACKGROUND)
+            .build()
+    };
+
+    Text::with_alignment(
+        "Settings",
+        Point::new(width / 2, height / 6),
+        MonoTextStyle::new(&FONT_10X20, D::Color::ACCENT.dimmed(settings.brightness)),
+        Alignment::Center,
+    )
+    .draw(display)
+    .unwrap();
+
+    for (i, &item) in MenuItem::ALL.iter().enumerate() {
+        let (style, marker, value) = match mode {
+            Mode::Menu(selected) if selected == item => {
+                (style(D::Color::ACCENT), '>', item.value(settings))
+            }
+            Mode::Edit(edited) if edited == item => {
+                let mut value: String<16> = String::new();
+                let _ = core::write!(value, "[{}]", item.value(settings));
+                (style(D::Color::DONE), '>', value)
+            }
+            _ => (style(D::Color::TEXT), ' ', item.value(settings)),
+        };
+
+        // Pad to clear any longer previous value
+        let mut row: String<40> = String::new();
+        let _ = core::write!(row, "{} {:<13}{:>10}    ", marker, item.label(), value);
+        let position = Point::new(10, height / 4 + 22 * i as i32);
+        Text::new(&row, position, style).draw(display).unwrap();
+    }
+}
+
+/// Draws `time` centered on `position`, blinking the colons: they are only
+/// shown on even seconds. Each group of digits and each colon is drawn on its
+/// own, so hiding the colons doesn't redraw the digits around them.
+fn draw_time<D>(
+    display: &mut D,
+    position: Point,
+    style: MonoTextStyle<'_, D::Color>,
+    time: &DateTime,
+    format: TimeFormat,
+) where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    let text = format_time(time, format);
+    let char_width = (style.font.character_size.width + style.font.character_spacing) as i32;
+    let mut next = position - Point::new(char_width * text.len() as i32 / 2, 0);
+
+    let mut colon_style = style;
+    if time.second % 2 == 1 {
+        colon_style.text_color = Some(D::Color::BACKGROUND);
+    }
+
+    for (i, digits) in text.split(':').enumerate() {
+        if i > 0 {
+            next = Text::new(":", next, colon_style).draw(display).unwrap();
+        }
+        next = Text::new(digits, next, style).draw(display).unwrap();
+    }
+}
+
+/// Renders the screens on the panel picked at boot, sending the frame buffer
+/// after each one on the OLED.
+#[embassy_executor::task]
+async fn display_task(panel: Panel) -> ! {
+    match panel {
+        Panel::Explorer(mut display) => run_display(&mut display, |_display| {}).await,
+        #[cfg(feature = "ssd1306")]
+        Panel::Oled(oled) => run_display(oled, flush).await,
+    }
+}
+
+/// Shows the boot splash, then renders the clock screen whenever a new state
+/// is signaled, so rendering is not tied to the network or sensor timing.
+/// `show` is called once a screen is drawn, for displays that buffer it.
+async fn run_display<D>(display: &mut D, mut show: impl FnMut(&mut D)) -> !
+where
+    D: DrawTarget,
+    D::Color: ClockColor,
+    D::Error: core::fmt::Debug,
+{
+    draw_splash(display, BootStep::JoiningWifi);
+    show(display);
+    let mut state = loop {
+        match select(BOOT_STEP.wait(), DISPLAY_STATE.wait()).await {
+            Either::First(step) => {
+                draw_splash(display, step);
+                show(display);
+            }
+            Either::Second(state) => break state,
+        }
+    };
+    display.clear(D::Color::BACKGROUND).unwrap();
+
+    let mut configuring = false;
+    let mut page = Page::Temperature;
+    let mut awake = true;
+    loop {
+        if awake {
+            // The screens don't overlap, start over when switching
+            if (state.mode != Mode::Clock) != configuring || state.page != page {
+                configuring = state.mode != Mode::Clock;
+                page = state.page;
+                display.clear(D::Color::BACKGROUND).unwrap();
+            }
+            draw_clock(display, &state);
+        }
+        show(display);
+
+        match select(DISPLAY_STATE.wait(), DISPLAY_POWER.wait()).await {
+            Either::First(latest) => state = latest,
+            Either::Second(on) => {
+                awake = on;
+                // Blank it, or start over from a clean screen
+                display.clear(D::Color::BACKGROUND).unwrap();
+            }
+        }
+    }
+}
+
+type SharedRtc = Mutex<CriticalSectionRawMutex, Rtc<'static, RTC>>;
+
+/// Why getting the time from the server failed.
+#[cfg(not(feature = "offline"))]
+#[derive(defmt::Format)]
+enum FetchError {
+    /// Connecting, sending the request or reading the response
+    Http(reqwless::Error),
+    /// The body is longer than [`TIME_BODY_MAX`]
+    TooLarge,
+    /// The body is not UTF-8
+    Body,
+    /// The body is not a valid time in any of the known formats
+    Parse,
+}
+
+#[cfg(not(feature = "offline"))]
+impl From<reqwless::Error> for FetchError {
+    fn from(e: reqwless::Error) -> Self {
+        FetchError::Http(e)
+    }
+}
+
+#[cfg(not(feature = "offline"))]
+type TimeClient<'a> = HttpClient<'a, TcpClient<'a, 1>, DnsSocket<'a>>;
+
+/// Gets the time from `url`, in UTC.
+#[cfg(not(feature = "offline"))]
+async fn fetch_time(
+    client: &mut TimeClient<'_>,
+    url: &str,
+    fallback: Option<&DateTime>,
+) -> Result<DateTime, FetchError> {
+    let mut rx_buffer = [0; HTTP_RX_BUFFER_SIZE];
+
+    info!("connecting to {}", url);
+    let mut request = client.request(Method::GET, url).await?;
+    let response = request.send(&mut rx_buffer).await?;
+
+    // Read piece by piece, whether the body is chunked or not, giving up on
+    // one too long for a time rather than running out of room
+    let mut body: Vec<u8, TIME_BODY_MAX> = Vec::new();
+    let mut reader = response.body().reader();
+    let mut chunk = [0; TIME_BODY_CHUNK];
+    loop {
+        let read = reader.read(&mut chunk).await?;
+        if read == 0 {
+            break;
+        }
+        body.extend_from_slice(&chunk[..read])
+            .map_err(|_e| FetchError::TooLarge)?;
+    }
+    let body = from_utf8(&body).map_err(|_e| FetchError::Body)?;
+    info!("Response body: {:?}", &body);
+
+    parse_time(body.as_bytes(), fallback).ok_or(FetchError::Parse)
+}
+
+/// Gets the local time from the time server, completing a partial response
+/// with the time currently kept by `rtc`.
+#[cfg(not(feature = "offline"))]
+async fn request_time(stack: Stack<'static>, rtc: &SharedRtc) -> Result<DateTime, FetchError> {
+    let current = rtc.lock().await.now().ok().and_then(|local| to_utc(&local));
+
+    let client_state = TcpClientState::<1, TCP_BUFFER_SIZE, TCP_BUFFER_SIZE>::new();
+    let tcp_client = TcpClient::new(stack, &client_state);
+    let dns_client = DnsSocket::new(stack);
+    let mut http_client = HttpClient::new(&tcp_client, &dns_client);
+
+    let utc = fetch_time(&mut http_client, TIME_URL, current.as_ref()).await?;
+    let datetime = to_local(&utc).ok_or(FetchError::Parse)?;
+    info!(
+        "Local datetime: {}-{}-{} {}:{}:{}",
+        datetime.year,
+        datetime.month,
+        datetime.day,
+        datetime.hour,
+        datetime.minute,
+        datetime.second
+    );
+
+    Ok(datetime)
+}
+
+/// Shifts a UTC time by [`TZ_OFFSET_MINUTES`], rolling over the date as needed.
+#[cfg(not(feature = "offline"))]
+fn to_local(utc: &DateTime) -> Option<DateTime> {
+    from_timestamp(timestamp(utc) + TZ_OFFSET_MINUTES as i64 * 60)
+}
+
+/// Inverse of [`to_local`].
+#[cfg(not(feature = "offline"))]
+fn to_utc(local: &DateTime) -> Option<DateTime> {
+    from_timestamp(timestamp(local) - TZ_OFFSET_MINUTES as i64 * 60)
+}
+
+/// Gets the time at boot, trying again with backoff as the time server may
+/// well come up after the clock, but only [`TIME_BOOTSTRAP_ATTEMPTS`] times so
+/// the clock still starts when it is down.
+#[cfg(not(feature = "offline"))]
+async fn bootstrap_time(stack: Stack<'static>, rtc: &SharedRtc) -> Option<DateTime> {
+    let mut backoff = TIME_BOOTSTRAP_BACKOFF;
+    for attempt in 1..=TIME_BOOTSTRAP_ATTEMPTS {
+        match request_time(stack, rtc).await {
+            Ok(datetime) => return Some(datetime),
+            Err(e) => warn!(
+                "Failed to get the time, attempt {}/{}: {}",
+                attempt, TIME_BOOTSTRAP_ATTEMPTS, e
+            ),
+        }
+        if attempt < TIME_BOOTSTRAP_ATTEMPTS {
+            Timer::after(backoff).await;
+            backoff = (backoff * 2).min(TIME_BOOTSTRAP_MAX_BACKOFF);
+        }
+    }
+    None
+}
+
+/// Periodically re-syncs the RTC with the time server to correct its drift.
+///
+/// Until the first successful sync it retries with the boot backoff rather
+/// than waiting for [`TIME_SYNC_INTERVAL`].
+#[cfg(not(feature = "offline"))]
+#[embassy_executor::task]
+async fn time_sync_task(stack: Stack<'static>, rtc: &'static SharedRtc) -> ! {
+    let mut backoff = TIME_BOOTSTRAP_BACKOFF;
+    loop {
+        if APP_STATE.lock().await.last_sync.is_some() {
+            Timer::after(TIME_SYNC_INTERVAL).await;
+        } else {
+            Timer::after(backoff).await;
+            backoff = (backoff * 2).min(TIME_BOOTSTRAP_MAX_BACKOFF);
+        }
+
+        let datetime = match request_time(stack, rtc).await {
+            Ok(datetime) => datetime,
+            Err(e) => {
+                warn!("Time re-sync failed: {}", e);
+                continue;
+            }
+        };
+
+        let mut rtc = rtc.lock().await;
+        if let Ok(now) = rtc.now() {
+            info!("RTC drift: {} s", timestamp(&now) - timestamp(&datetime));
+        }
+        if rtc.set_datetime(datetime).is_err() {
+            error!("Failed to set the RTC");
+            continue;
+        }
+        APP_STATE.lock().await.last_sync = Some(Instant::now());
+    }
+}
+
+/// A reading reported to the telemetry server.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+struct Telemetry {
+    time: DateTime,
+    temperature: f64,
+}
+
+/// Latest reading, the telemetry task only ever sends the most recent one.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+static TELEMETRY: Signal<CriticalSectionRawMutex, Telemetry> = Signal::new();
+
+/// Sends a reading to the telemetry server as JSON over HTTPS.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+async fn post_telemetry(
+    stack: Stack<'static>,
+    tls_config: TlsConfig<'_>,
+    telemetry: &Telemetry,
+) -> Result<(), reqwless::Error> {
+    let mut rx_buffer = [0; TCP_BUFFER_SIZE];
+
+    let client_state = TcpClientState::<1, TCP_BUFFER_SIZE, TCP_BUFFER_SIZE>::new();
+    let tcp_client = TcpClient::new(stack, &client_state);
+    let dns_client = DnsSocket::new(stack);
+
+    let mut http_client = HttpClient::new_with_tls(&tcp_client, &dns_client, tls_config);
+
+    let time = &telemetry.time;
+    let mut body: String<80> = String::new();
+    let _ = core::write!(
+        body,
+        "{{\"datetime\":\"{:04}-{:02}-{:02}T{:02}:{:02}:{:02}\",\"temperature\":{:.1}}}",
+        time.year,
+        time.month,
+        time.day,
+        time.hour,
+        time.minute,
+        time.second,
+        telemetry.temperature
+    );
+
+    let mut request = http_client
+        .request(Method::POST, TELEMETRY_URL)
+        .await?
+        .body(body.as_bytes())
+        .content_type(ContentType::ApplicationJson);
+    let response = request.send(&mut rx_buffer).await?;
+    if !response.status.is_successful() {
+        warn!("Telemetry rejected: {:?}", response.status);
+    }
+
+    Ok(())
+}
+
+/// Reports readings to the telemetry server, retrying failed sends (e.g. TLS
+/// handshake errors) with backoff. Failures are only logged, the clock keeps
+/// running regardless.
+#[cfg(all(feature = "telemetry", not(feature = "offline")))]
+#[embassy_executor::task]
+async fn telemetry_task(
+    stack: Stack<'static>,
+    mut seed: u64,
+    tls_read_buffer: &'static mut [u8],
+    tls_write_buffer: &'static mut [u8],
+) -> ! {
+    loop {
+        let telemetry = TELEMETRY.wait().await;
+
+        let mut backoff = TELEMETRY_BACKOFF;
+        for attempt in 1..=TELEMETRY_ATTEMPTS {
+            // A fresh seed for every handshake
+            seed = seed.wrapping_add(1);
+            let tls_config =
+                TlsConfig::new(seed, tls_read_buffer, tls_write_buffer, TlsVerify::None);
+
+            match post_telemetry(stack, tls_config, &telemetry).await {
+                Ok(()) => break,
+                Err(e) => warn!(
+                    "Telemetry attempt {}/{} failed: {:?}",
+                    attempt, TELEMETRY_ATTEMPTS, e
+                ),
+            }
+
+            if attempt == TELEMETRY_ATTEMPTS {
+                error!("Giving up on this telemetry report");
+            } else {
+                Timer::after(backoff).await;
+                backoff *= 2;
+            }
+        }
+
+        Timer::after(TELEMETRY_INTERVAL).await;
+    }
+}
+
+/// Shows the panic on the display, as `panic_probe` only reports it over RTT
+/// which is useless for a clock with no debugger attached.
+#[panic_handler]
+fn panic(info: &PanicInfo) -> ! {
+    cortex_m::interrupt::disable();
+    error!("{}", Display2Format(info));
+
+    // The display belongs to `main`, so take its peripherals over
+    let p = unsafe { embassy_rp::Peripherals::steal() };
+
+    #[cfg(feature = "ssd1306")]
+    if OLED_IN_USE.load(Ordering::Relaxed) {
+        let i2c = I2c::new_blocking(p.I2C0, p.PIN_21, p.PIN_20, I2cConfig::default());
+        let interface = I2CDisplayInterface::new(i2c);
+        let mut oled = Ssd1306::new(interface, DisplaySize128x64, DisplayRotation::Rotate0)
+            .into_buffered_graphics_mode();
+        if oled.init().is_ok() {
+            draw_panic(&mut oled, info, BinaryColor::On);
+            let _ = oled.flush();
+        }
+        cortex_m::asm::udf()
+    }
+
+    let mut config = embassy_rp::spi::Config::default();
+    config.frequency = DISPLAY_FREQ;
+    config.phase = embassy_rp::spi::Phase::CaptureOnSecondTransition;
+    config.polarity = embassy_rp::spi::Polarity::IdleHigh;
+    let spi = embassy_rp::spi::Spi::new_blocking(p.SPI0, p.PIN_18, p.PIN_19, p.PIN_4, config);
+    let cs = Output::new(p.PIN_17, Level::High);
+    let dc = Output::new(p.PIN_16, Level::Low);
+    let rst = Output::new(p.PIN_0, Level::Low);
+    let di = SPIDeviceInterface::new(ExclusiveDevice::new_no_delay(spi, cs), dc);
+    let mut display = st7789::ST7789::new(di, rst, DISPLAY_WIDTH, DISPLAY_HEIGHT);
+
+    if display.init(&mut embassy_time::Delay).is_ok()
+        && display.set_orientation(DISPLAY_ORIENTATION).is_ok()
+        && display.clear(Rgb565::RED).is_ok()
+    {
+        draw_panic(&mut display, info, Rgb565::WHITE);
+    }
+
+    cortex_m::asm::udf()
+}
+
+/// Writes the panic message in `color`, wrapped to the width of `display`.
+fn draw_panic<D: DrawTarget>(display: &mut D, info: &PanicInfo, color: D::Color) {
+    let mut message: String<512> = String::new();
+    let _ = core::write!(message, "{}", info);
+
+    let style = MonoTextStyle::new(&FONT_7X13_BOLD, color);
+    let width = (display.bounding_box().size.width as usize - 2) / 7;
+    let mut line: String<34> = String::new();
+    let mut y = 15;
+    for c in message.chars().chain(Some('\n')) {
+        if c == '\n' || line.len() >= width || line.push(c).is_err() {
+            let _ = Text::new(&line, Point::new(2, y), style).draw(display);
+            line.clear();
+            y += 15;
+            if c != '\n' {
+                let _ = line.push(c);
+            }
+        }
+    }
+}
+
+bind_interrupts!(struct Irqs {
+    PIO0_IRQ_0 => InterruptHandler<PIO0>;
+});
+
+#[embassy_executor::main]
+async fn main(spawner: Spawner) {
+    let peripherals = embassy_rp::init(Default::default());
+
+    // The BMP280 and the EEPROM share the I2C bus, along with the OLED if any
+    let i2c = I2c::new_blocking(
+        peripherals.I2C0,
+        peripherals.PIN_21,
+        peripherals.PIN_20,
+        I2cConfig::default(),
+    );
+    static I2C_BUS: StaticCell<I2cBus> = StaticCell::new();
+    let i2c_bus = I2C_BUS.init(embassy_sync::blocking_mutex::Mutex::new(RefCell::new(i2c)));
+
+    // The OLED replaces the Pico Explorer display, unless it doesn't answer
+    #[cfg(feature = "ssd1306")]
+    let oled = init_oled(i2c_bus);
+    #[cfg(not(feature = "ssd1306"))]
+    let oled: Option<Panel> = None;
+    let mut panel = match oled {
+        Some(oled) => oled,
+        None => {
+            info!("Initializing display...");
+
+            // ************** Display initialization - DO NOT MODIFY! *****************
+            let miso = peripherals.PIN_4;
+            let display_cs = peripherals.PIN_17;
+            let mosi = peripherals.PIN_19;
+            let clk = peripherals.PIN_18;
+            let rst = peripherals.PIN_0;
+            let dc = peripherals.PIN_16;
+            let mut display_config = embassy_rp::spi::Config::default();
+            display_config.frequency = DISPLAY_FREQ;
+            display_config.phase = embassy_rp::spi::Phase::CaptureOnSecondTransition;
+            display_config.polarity = embassy_rp::spi::Polarity::IdleHigh;
+
+            // Init SPI
+            let spi: embassy_rp::spi::Spi<'_, _, embassy_rp::spi::Blocking> =
+                embassy_rp::spi::Spi::new_blocking(
+                    peripherals.SPI0,
+                    clk,
+                    mosi,
+                    miso,
+                    display_config.clone(),
+                );
+            static SPI_BUS: StaticCell<DisplaySpiBus> = StaticCell::new();
+            let spi_bus = SPI_BUS.init(embassy_sync::blocking_mutex::Mutex::new(
+                core::cell::RefCell::new(spi),
+            ));
+
+            let display_spi =
+                embassy_embedded_hal::shared_bus::blocking::spi::SpiDeviceWithConfig::new(
+                    spi_bus,
+                    embassy_rp::gpio::Output::new(display_cs, embassy_rp::gpio::Level::High),
+                    display_config,
+                );
+
+            let dc = embassy_rp::gpio::Output::new(dc, embassy_rp::gpio::Level::Low);
+            let rst = embassy_rp::gpio::Output::new(rst, embassy_rp::gpio::Level::Low);
+            let di = SPIDeviceInterface::new(display_spi, dc);
+
+            // Init ST7789 LCD
+            let mut display = st7789::ST7789::new(di, rst, DISPLAY_WIDTH, DISPLAY_HEIGHT);
+            display.init(&mut embassy_time::Delay).unwrap();
+            display.set_orientation(DISPLAY_ORIENTATION).unwrap();
+            display.clear(<embedded_graphics::pixelcolor::Rgb565 as embedded_graphics::pixelcolor::RgbColor>::BLACK).unwrap();
+            // ************************************************************************
+
+            info!("Display initialization finished!");
+            Panel::Explorer(display)
+        }
+    };
+
+    // The offline demo simulates the readings, it needs no BMP280
+    #[cfg(not(feature = "offline"))]
+    let bmp = {
+        let mut bmp = BMP280::new(I2cDevice::new(i2c_bus)).unwrap();
+        bmp.apply_preset(Preset::HandheldLowPower);
+        bmp
+    };
+    spawner
+        .spawn(sensor_task(TemperatureSensors {
+            #[cfg(not(feature = "offline"))]
+            bmp,
+            #[cfg(not(feature = "offline"))]
+            internal: InternalSensor {
+                adc: Adc::new_blocking(peripherals.ADC, AdcConfig::default()),
+                channel: AdcChannel::new_temp_sensor(peripherals.ADC_TEMP_SENSOR),
+            },
+        }))
+        .unwrap();
+
+    // Probe the EEPROM once, so a missing chip means a single warning and no
+    // more accesses rather than a failed transaction every time
+    let mut eeprom = Eeprom24x::new_24x256(I2cDevice::new(i2c_bus), SlaveAddr::default());
+    let mut eeprom = match eeprom.read_current_address() {
+        Ok(_) => Some(eeprom),
+        Err(_e) => {
+            warn!("No EEPROM found, settings will not be saved");
+            None
+        }
+    };
+
+    // Pico Explorer buttons, pulled low when pressed
+    let button_a = Input::new(peripherals.PIN_12, Pull::Up);
+    let button_x = Input::new(peripherals.PIN_14, Pull::Up);
+    let button_y = Input::new(peripherals.PIN_15, Pull::Up);
+
+    let mut saved = [0; Settings::LEN];
+    let settings = if panel.factory_reset_requested(&button_x, &button_y).await {
+        // Erased EEPROM, read back as the defaults
+        let wiped = eeprom
+            .as_mut()
+            .map(|eeprom| eeprom.write_page(EEPROM_SETTINGS, &[0xFF; Settings::LEN]));
+        let outcome = match wiped {
+            Some(Ok(())) => "Settings restored to defaults",
+            Some(Err(_e)) => {
+                warn!("Failed to clear the settings in the EEPROM");
+                "Failed to clear the EEPROM"
+            }
+            None => "No EEPROM, nothing to clear",
+        };
+        info!("Factory reset: {}", outcome);
+        panel.notice("Factory reset", outcome);
+        Timer::after(FACTORY_RESET_NOTICE).await;
+        panel.clear();
+        Settings::default()
+    } else {
+        match eeprom
+            .as_mut()
+            .map(|eeprom| eeprom.read_data(EEPROM_SETTINGS, &mut saved))
+        {
+            Some(Ok(())) => Settings::from_bytes(saved),
+            Some(Err(_e)) => {
+                warn!("Failed to read the settings from the EEPROM");
+                Settings::default()
+            }
+            None => Settings::default(),
+        }
+    };
+    // Published right away, so the tasks running during the boot already
+    // follow the saved settings
+    APP_STATE.lock().await.settings = settings;
+
+    spawner.spawn(display_task(panel)).unwrap();
+
+    let mut led_rg = Pwm::new_output_ab(
+        peripherals.PWM_SLICE3,
+        peripherals.PIN_6,
+        peripherals.PIN_7,
+        PwmConfig::default(),
+    );
+    let mut led_b = Pwm::new_output_b(
+        peripherals.PWM_SLICE1,
+        peripherals.PIN_3,
+        PwmConfig::default(),
+    );
+    let (red, green) = led_rg.split_by_ref();
+    let (_, blue) = led_b.split_by_ref();
+    let mut led = RgbLed::new(red.unwrap(), green.unwrap(), blue.unwrap());
+    led.set_brightness(settings.brightness);
+
+    // Piezo buzzer on the spare GP8, the only output of its slice
+    let buzzer = Pwm::new_output_a(
+        peripherals.PWM_SLICE4,
+        peripherals.PIN_8,
+        PwmConfig::default(),
+    );
+    spawner.spawn(buzzer_task(Buzzer::new(buzzer))).unwrap();
+
+    spawner
+        .spawn(buttons_task(button_a, button_x, button_y))
+        .unwrap();
+
+    // Optional rotary encoder on the spare GP26 and GP27, the pull-ups keeping
+    // them idle when none is connected
+    let encoder = Encoder::new(
+        Input::new(peripherals.PIN_26, Pull::Up),
+        Input::new(peripherals.PIN_27, Pull::Up),
+    );
+    spawner.spawn(encoder_task(encoder)).unwrap();
+
+    static CLOCK: StaticCell<SharedRtc> = StaticCell::new();
+    let rtc = CLOCK.init(Mutex::new(Rtc::new(peripherals.RTC)));
+
+    #[cfg(not(feature = "offline"))]
+    let stack = {
+        let fw = unsafe { core::slice::from_raw_parts(0x10100000 as *const u8, 230321) };
+        let clm = unsafe { core::slice::from_raw_parts(0x10140000 as *const u8, 4752) };
+
+        let pwr = Output::new(peripherals.PIN_23, Level::Low);
+        let cs = Output::new(peripherals.PIN_25, Level::High);
+        let mut pio = Pio::new(peripherals.PIO0, Irqs);
+        let spi = PioSpi::new(
+            &mut pio.common,
+            pio.sm0,
+            pio.irq0,
+            cs,
+            peripherals.PIN_24,
+            peripherals.PIN_29,
+            peripherals.DMA_CH0,
+        );
+
+        static STATE: StaticCell<cyw43::State> = StaticCell::new();
+        let state = STATE.init(cyw43::State::new());
+        let (net_device, mut control, runner) = cyw43::new(state, pwr, spi, fw).await;
+        spawner.spawn(cyw43_task(runner)).unwrap();
+
+        control.init(clm).await;
+        control
+            .set_power_management(cyw43::PowerManagementMode::PowerSave)
+            .await;
+
+        // Generate random seed
+        let seed = 69;
+
+        // Init network stack
+        static RESOURCES: StaticCell<StackResources<STACK_SOCKETS>> = StaticCell::new();
+        let (stack, runner) = embassy_net::new(
+            net_device,
+            NETWORK_CONFIG.stack_config(),
+            RESOURCES.init(StackResources::new()),
+            seed,
+        );
+
+        spawner.spawn(net_task(runner)).unwrap();
+
+        BOOT_STEP.signal(BootStep::JoiningWifi);
+
+        // Start with the network that was joined last time
+        let first = match eeprom
+            .as_mut()
+            .map(|eeprom| eeprom.read_byte(EEPROM_LAST_NETWORK))
+        {
+            Some(Ok(index)) => index as usize % WIFI_NETWORKS.len(),
+            _ => 0,
+        };
+        let network = join_wifi(&mut control, first).await;
+        if network != first {
+            if let Some(eeprom) = eeprom.as_mut() {
+                if let Err(_e) = eeprom.write_byte(EEPROM_LAST_NETWORK, network as u8) {
+                    warn!("Failed to save the WiFi network to the EEPROM");
+                }
+            }
+        }
+
+        // Keeps the connection up from now on
+        spawner.spawn(wifi_task(control, stack, network)).unwrap();
+
+        BOOT_STEP.signal(BootStep::WaitingForNetwork);
+
+        info!("waiting for link up...");
+        while !stack.is_link_up() {
+            Timer::after(LINK_POLL).await;
+        }
+        info!("Link is up!");
+
+        // A static address is configured right away
+        if let NetworkConfig::Dhcp = NETWORK_CONFIG {
+            info!("waiting for DHCP...");
+            stack.wait_config_up().await;
+            info!("DHCP is now up!");
+        }
+        BOOT_STEP.signal(BootStep::GettingTime);
+        match bootstrap_time(stack, rtc).await {
+            Some(datetime) => {
+                if rtc.lock().await.set_datetime(datetime).is_err() {
+                    error!("Failed to set the RTC");
+                    return; // handle the error
+                }
+                APP_STATE.lock().await.last_sync = Some(Instant::now());
+            }
+            // Whatever the RTC kept over a soft reset, if anything, until
+            // the time sync gets through
+            None => warn!("Starting without the time, retrying in the background"),
+        }
+
+        spawner.spawn(time_sync_task(stack, rtc)).unwrap();
+
+        // Only allocated with telemetry, the time is fetched over plain HTTP
+        #[cfg(feature = "telemetry")]
+        {
+            static TLS_READ_BUFFER: StaticCell<[u8; TLS_BUFFER_SIZE]> = StaticCell::new();
+            static TLS_WRITE_BUFFER: StaticCell<[u8; TLS_BUFFER_SIZE]> = StaticCell::new();
+            spawner
+                .spawn(telemetry_task(
+                    stack,
+                    seed,
+                    TLS_READ_BUFFER.init([0; TLS_BUFFER_SIZE]),
+                    TLS_WRITE_BUFFER.init([0; TLS_BUFFER_SIZE]),
+                ))
+                .unwrap();
+        }
+
+        stack
+    };
+
+    #[cfg(feature = "offline")]
+    {
+        info!("Offline demo, skipping the network");
+        if rtc.lock().await.set_datetime(OFFLINE_TIME).is_err() {
+            error!("Failed to set the RTC");
+            return; // handle the error
+        }
+    }
+
+    BOOT_STEP.signal(BootStep::Ready);
+    spawner.spawn(idle_task()).unwrap();
+    Timer::after(WELCOME_HOLD).await;
+
+    let mut temperatures = TEMPERATURE.receiver().unwrap();
+    let mut barometers = BAROMETER.receiver().unwrap();
+    loop {
+        let time = rtc.lock().await.now().ok();
+        let temperature = temperatures.try_get().flatten();
+        let barometer = barometers.try_get().flatten();
+        #[cfg(not(feature = "offline"))]
+        let status = if stack.is_link_up() {
+            "WiFi up"
+        } else {
+            "WiFi down"
+        };
+        #[cfg(feature = "offline")]
+        let status = "Offline demo";
+
+        {
+            let mut state = APP_STATE.lock().await;
+            state.time = time;
+            state.temperature = temperature;
+            state.barometer = barometer;
+            if let Some((r, g, b)) = state.led_color() {
+                led.set_rgb(r, g, b);
+            }
+            #[cfg(all(feature = "telemetry", not(feature = "offline")))]
+            if let (Some(time), Some(temperature)) = (state.time.clone(), temperature) {
+                TELEMETRY.signal(Telemetry { time, temperature });
+            }
+            DISPLAY_STATE.signal(state.display_state(status));
+        }
+
+        // Redraw right away after a press, otherwise every DISPLAY_REFRESH
+        let refresh = Timer::after(DISPLAY_REFRESH);
+        let Either::First(button) = select(BUTTONS.receive(), refresh).await else {
+            continue;
+        };
+        let mut state = APP_STATE.lock().await;
+        if let Some(settings) = state.press(button) {
+            let saved = eeprom
+                .as_mut()
+                .map(|eeprom| eeprom.write_page(EEPROM_SETTINGS, &settings.to_bytes()));
+            if let Some(Err(_e)) = saved {
+                warn!("Failed to save the settings to the EEPROM");
+            }
+        }
+        if led.brightness() != state.settings.brightness {
+            led.set_brightness(state.settings.brightness);
+        }
     }
 }
@@ -0,0 +1,121 @@
+//! Values of the clock screens and menu, and their text.
+
+use super::DateTime;
+use core::fmt::Write;
+use embedded_nov_2024::bmp280::celsius_to_fahrenheit;
+use heapless::String;
+
+/// Unit used to display temperatures.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl TemperatureUnit {
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            TemperatureUnit::Celsius => TemperatureUnit::Fahrenheit,
+            TemperatureUnit::Fahrenheit => TemperatureUnit::Celsius,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            TemperatureUnit::Celsius => "Celsius",
+            TemperatureUnit::Fahrenheit => "Fahrenheit",
+        }
+    }
+}
+
+/// How the time is displayed.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum TimeFormat {
+    /// 00:00:00 to 23:59:59
+    H24,
+    /// 12:00:00 AM to 11:59:59 PM
+    H12,
+}
+
+impl TimeFormat {
+    pub(crate) fn toggled(self) -> Self {
+        match self {
+            TimeFormat::H24 => TimeFormat::H12,
+            TimeFormat::H12 => TimeFormat::H24,
+        }
+    }
+
+    pub(crate) fn label(self) -> &'static str {
+        match self {
+            TimeFormat::H24 => "24 hour",
+            TimeFormat::H12 => "12 hour",
+        }
+    }
+}
+
+/// A temperature threshold, counted in half degrees Celsius so that stepping
+/// it up and down never accumulates rounding errors.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct Threshold(pub(crate) i16);
+
+impl Threshold {
+    pub(crate) const fn from_celsius(celsius: i16) -> Self {
+        Threshold(celsius * 2)
+    }
+
+    /// Half a degree warmer.
+    pub(crate) fn increment(self) -> Self {
+        Threshold(self.0.saturating_add(1))
+    }
+
+    /// Half a degree colder.
+    pub(crate) fn decrement(self) -> Self {
+        Threshold(self.0.saturating_sub(1))
+    }
+
+    pub(crate) fn celsius(self) -> f64 {
+        self.0 as f64 / 2.0
+    }
+}
+
+/// Formats a temperature given in Celsius as e.g. "23.5°C".
+pub(crate) fn format_temperature(temp_c: f64, unit: TemperatureUnit) -> String<16> {
+    let (value, symbol) = match unit {
+        TemperatureUnit::Celsius => (temp_c, 'C'),
+        TemperatureUnit::Fahrenheit => (celsius_to_fahrenheit(temp_c), 'F'),
+    };
+
+    let mut s = String::new();
+    // Pad to clear any longer previous value
+    let _ = core::write!(s, "{:.1}°{}  ", value, symbol);
+    s
+}
+
+/// Formats a threshold as e.g. "22.5°C" or "72.5°F", exactly to the tenth
+/// of a degree, which half a degree Celsius always is in Fahrenheit too.
+pub(crate) fn format_threshold(threshold: Threshold, unit: TemperatureUnit) -> String<16> {
+    let (tenths, symbol) = match unit {
+        TemperatureUnit::Celsius => (threshold.0 as i32 * 5, 'C'),
+        TemperatureUnit::Fahrenheit => (threshold.0 as i32 * 9 + 320, 'F'),
+    };
+    let sign = if tenths < 0 { "-" } else { "" };
+    let tenths = tenths.unsigned_abs();
+
+    let mut s = String::new();
+    let _ = core::write!(s, "{}{}.{}°{}", sign, tenths / 10, tenths % 10, symbol);
+    s
+}
+
+/// Formats a time as "HH:MM:SS", followed by "AM" or "PM" in 12 hour format.
+pub(crate) fn format_time(time: &DateTime, format: TimeFormat) -> String<11> {
+    let (hour, suffix) = match format {
+        TimeFormat::H24 => (time.hour, ""),
+        TimeFormat::H12 if time.hour < 12 => ((time.hour + 11) % 12 + 1, " AM"),
+        TimeFormat::H12 => ((time.hour + 11) % 12 + 1, " PM"),
+    };
+
+    let mut s = String::new();
+    let _ = core::write!(s, "{:02}:{:02}:{:02}", hour, time.minute, time.second);
+    let _ = s.push_str(suffix);
+    s
+}
@@ -0,0 +1,287 @@
+//! Times sent by the time servers, and the calendar arithmetic to read them.
+
+use super::{DateTime, DayOfWeek};
+use serde::Deserialize;
+
+/// Reads a time server response body, in UTC, completing a partial one with
+/// the fields of `fallback`. `None` if it is in none of the known shapes.
+pub(crate) fn parse_time(body: &[u8], fallback: Option<&DateTime>) -> Option<DateTime> {
+    // A complete ISO-8601 time goes first, servers sending both it and
+    // separate fields (timeapi.io) may name those in ways not recognized,
+    // which would leave them to be filled in from `fallback`
+    let datetime = match serde_json_core::de::from_slice::<IsoApiResponse>(body) {
+        Ok((output, _used)) => parse_iso8601(output.date_time),
+        Err(_e) => None,
+    };
+    let datetime = datetime.or_else(|| {
+        let (output, _used) = serde_json_core::de::from_slice::<ApiResponse>(body).ok()?;
+        output.datetime(fallback)
+    });
+    datetime.or_else(|| {
+        let (output, _used) = serde_json_core::de::from_slice::<FlatApiResponse>(body).ok()?;
+        ApiResponse::from(output).datetime(fallback)
+    })
+}
+
+// Every field is optional and unknown ones are skipped, so a server sending
+// more or less than this doesn't make the whole response unreadable
+#[derive(Deserialize)]
+struct ApiDate {
+    year: Option<u16>,
+    month: Option<u16>,
+    day: Option<u16>,
+}
+
+#[derive(Deserialize)]
+struct ApiTime {
+    hour: Option<u16>,
+    minite: Option<u16>,
+    second: Option<u16>,
+}
+
+/// Time server response, in UTC: no offset is sent, so none is applied.
+#[derive(Deserialize)]
+pub(crate) struct ApiResponse {
+    time: Option<ApiTime>,
+    date: Option<ApiDate>,
+}
+
+impl ApiResponse {
+    /// Builds the datetime from the fields that were sent, taking the missing
+    /// ones from `fallback`. `None` if neither the date nor the time was sent.
+    pub(crate) fn datetime(&self, fallback: Option<&DateTime>) -> Option<DateTime> {
+        if self.date.is_none() && self.time.is_none() {
+            return None;
+        }
+        let (date, time) = (self.date.as_ref(), self.time.as_ref());
+        let field = |sent: Option<u16>, current: fn(&DateTime) -> u16| -> Option<u8> {
+            sent.or_else(|| fallback.map(current))?.try_into().ok()
+        };
+
+        datetime(
+            date.and_then(|d| d.year)
+                .or_else(|| fallback.map(|f| f.year))?,
+            field(date.and_then(|d| d.month), |f| f.month.into())?,
+            field(date.and_then(|d| d.day), |f| f.day.into())?,
+            field(time.and_then(|t| t.hour), |f| f.hour.into())?,
+            field(time.and_then(|t| t.minite), |f| f.minute.into())?,
+            field(time.and_then(|t| t.second), |f| f.second.into())?,
+        )
+    }
+}
+
+/// Flat variant of [`ApiResponse`], `{"year":...,"hour":...}`, sent by
+/// servers that don't group the date and time fields. In UTC, like the
+/// nested shape.
+#[derive(Deserialize)]
+pub(crate) struct FlatApiResponse {
+    year: Option<u16>,
+    month: Option<u16>,
+    day: Option<u16>,
+    hour: Option<u16>,
+    #[serde(alias = "minite")]
+    minute: Option<u16>,
+    #[serde(alias = "seconds")]
+    second: Option<u16>,
+}
+
+impl From<FlatApiResponse> for ApiResponse {
+    fn from(flat: FlatApiResponse) -> Self {
+        // A group none of whose fields were sent counts as missing
+        let date = ApiDate {
+            year: flat.year,
+            month: flat.month,
+            day: flat.day,
+        };
+        let time = ApiTime {
+            hour: flat.hour,
+            minite: flat.minute,
+            second: flat.second,
+        };
+        let date_sent = date.year.is_some() || date.month.is_some() || date.day.is_some();
+        let time_sent = time.hour.is_some() || time.minite.is_some() || time.second.is_some();
+        ApiResponse {
+            date: date_sent.then_some(date),
+            time: time_sent.then_some(time),
+        }
+    }
+}
+
+/// Response of public time APIs such as WorldTimeAPI (`datetime`) and
+/// timeapi.io (`dateTime`), which carry an ISO-8601 string.
+#[derive(Deserialize)]
+struct IsoApiResponse<'a> {
+    #[serde(rename = "dateTime", alias = "datetime")]
+    date_time: &'a str,
+}
+
+/// Parses an ISO-8601 string into UTC, ignoring fractional seconds. The time
+/// is shifted by the `Z` or `±hh:mm` offset if there is one and taken as UTC
+/// otherwise, so timeapi.io must be asked for the UTC zone.
+pub(crate) fn parse_iso8601(s: &str) -> Option<DateTime> {
+    let bytes = s.as_bytes();
+    if bytes.len() < 19
+        || bytes[4] != b'-'
+        || bytes[7] != b'-'
+        || !matches!(bytes[10], b'T' | b' ')
+        || bytes[13] != b':'
+        || bytes[16] != b':'
+    {
+        return None;
+    }
+
+    let datetime = datetime(
+        digits(s.get(0..4)?)?,
+        digits(s.get(5..7)?)?.try_into().ok()?,
+        digits(s.get(8..10)?)?.try_into().ok()?,
+        digits(s.get(11..13)?)?.try_into().ok()?,
+        digits(s.get(14..16)?)?.try_into().ok()?,
+        digits(s.get(17..19)?)?.try_into().ok()?,
+    )?;
+
+    let mut rest = &s[19..];
+    if let Some(fraction) = rest.strip_prefix('.') {
+        rest = fraction.trim_start_matches(|c: char| c.is_ascii_digit());
+        if rest.len() == fraction.len() {
+            return None;
+        }
+    }
+    let offset_minutes = match rest.as_bytes() {
+        [] | [b'Z'] => 0,
+        [sign @ (b'+' | b'-'), ..] => {
+            let sign = if *sign == b'-' { -1 } else { 1 };
+            let offset = &rest[1..];
+            let (hours, minutes) = match offset.len() {
+                5 if offset.as_bytes()[2] == b':' => (offset.get(0..2)?, offset.get(3..5)?),
+                4 => (offset.get(0..2)?, offset.get(2..4)?),
+                _ => return None,
+            };
+            let hours = digits(hours)? as i64;
+            let minutes = digits(minutes)? as i64;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            sign * (hours * 60 + minutes)
+        }
+        _ => return None,
+    };
+
+    from_timestamp(timestamp(&datetime) - offset_minutes * 60)
+}
+
+/// Parses a field made of ASCII digits only, unlike [`str::parse`] which
+/// also takes a leading `+`.
+fn digits(s: &str) -> Option<u16> {
+    if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// Builds an RTC datetime, rejecting out of range fields.
+pub(crate) fn datetime(
+    year: u16,
+    month: u8,
+    day: u8,
+    hour: u8,
+    minute: u8,
+    second: u8,
+) -> Option<DateTime> {
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    Some(DateTime {
+        year,
+        month,
+        day,
+        day_of_week: day_of_week(year, month, day),
+        hour,
+        minute,
+        second,
+    })
+}
+
+/// Days in `month` (1 to 12) of `year`, in the Gregorian calendar.
+fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        // Leap years are those divisible by 4, except centuries not divisible by 400
+        2 => match (year % 4, year % 100, year % 400) {
+            (_, _, 0) => 29,
+            (_, 0, _) => 28,
+            (0, _, _) => 29,
+            _ => 28,
+        },
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Sakamoto's algorithm.
+fn day_of_week(year: u16, month: u8, day: u8) -> DayOfWeek {
+    const OFFSETS: [i32; 12] = [0, 3, 2, 5, 0, 3, 5, 1, 4, 6, 2, 4];
+    // Signed, as January and February of year 0 count as year -1
+    let y = year as i32 - if month < 3 { 1 } else { 0 };
+    let days = y + y.div_euclid(4) - y.div_euclid(100) + y.div_euclid(400);
+    match (days + OFFSETS[month as usize - 1] + day as i32).rem_euclid(7) {
+        0 => DayOfWeek::Sunday,
+        1 => DayOfWeek::Monday,
+        2 => DayOfWeek::Tuesday,
+        3 => DayOfWeek::Wednesday,
+        4 => DayOfWeek::Thursday,
+        5 => DayOfWeek::Friday,
+        _ => DayOfWeek::Saturday,
+    }
+}
+
+/// Seconds since the Unix epoch.
+pub(crate) fn timestamp(datetime: &DateTime) -> i64 {
+    // Days from civil, with years starting in March
+    let month = datetime.month as i64;
+    let year = datetime.year as i64 - if month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + datetime.day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    days * 86_400
+        + datetime.hour as i64 * 3_600
+        + datetime.minute as i64 * 60
+        + datetime.second as i64
+}
+
+/// Inverse of [`timestamp`].
+pub(crate) fn from_timestamp(timestamp: i64) -> Option<DateTime> {
+    let seconds = timestamp.rem_euclid(86_400);
+
+    // Civil from days, with years starting in March
+    let days = timestamp.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    datetime(
+        u16::try_from(year).ok()?,
+        month as u8,
+        day as u8,
+        (seconds / 3_600) as u8,
+        (seconds % 3_600 / 60) as u8,
+        (seconds % 60) as u8,
+    )
+}